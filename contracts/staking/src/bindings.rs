@@ -0,0 +1,109 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Binary, Coin, CosmosMsg, CustomMsg, CustomQuery, QuerierWrapper, QueryRequest, StdResult, Uint128};
+
+// BindingMsg are the chain specific messages this contract sends through CosmosMsg::Custom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingMsg {
+    /// CreateDenom creates the token-factory denom `factory/{contract}/{subdenom}`, owned by the contract
+    CreateDenom { subdenom: String },
+    /// MintTokens mints `amount` of a denom owned by the contract to `mint_to_address`
+    MintTokens {
+        denom: String,
+        amount: Uint128,
+        mint_to_address: String,
+    },
+    /// BurnTokens burns `amount` of a denom owned by the contract from `burn_from_address`
+    BurnTokens {
+        denom: String,
+        amount: Uint128,
+        burn_from_address: String,
+    },
+    /// WithdrawRewards withdraws the Archway rewards records of the contract, either the
+    /// first `records_limit` records or the ones listed in `record_ids`
+    WithdrawRewards {
+        records_limit: Option<u64>,
+        record_ids: Vec<u64>,
+    },
+}
+
+impl CustomMsg for BindingMsg {}
+
+impl From<BindingMsg> for CosmosMsg<BindingMsg> {
+    fn from(msg: BindingMsg) -> Self {
+        CosmosMsg::Custom(msg)
+    }
+}
+
+// BindingQuery are the chain specific queries this contract sends through QueryRequest::Custom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingQuery {
+    /// FullDenom returns the token-factory denom `creator_addr` gets for `subdenom`
+    FullDenom { creator_addr: String, subdenom: String },
+    /// RewardsRecords returns the Archway rewards records waiting to be withdrawn by `rewards_address`
+    RewardsRecords {
+        rewards_address: String,
+        pagination: Option<PageRequest>,
+    },
+}
+
+impl CustomQuery for BindingQuery {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FullDenomResponse {
+    pub denom: String,
+}
+
+// query_full_denom returns the token-factory denom `creator_addr` gets for `subdenom`
+pub fn query_full_denom(
+    querier: &QuerierWrapper<BindingQuery>,
+    creator_addr: String,
+    subdenom: String,
+) -> StdResult<String> {
+    let request = QueryRequest::Custom(BindingQuery::FullDenom { creator_addr, subdenom });
+    let res: FullDenomResponse = querier.query(&request)?;
+    Ok(res.denom)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PageRequest {
+    pub key: Option<Binary>,
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+    pub count_total: bool,
+    pub reverse: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardsRecord {
+    pub id: u64,
+    pub rewards_address: String,
+    pub rewards: Vec<Coin>,
+    pub calculated_height: i64,
+    pub calculated_time: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardsRecordsResponse {
+    pub records: Vec<RewardsRecord>,
+}
+
+// query_rewards_records returns up to `limit` Archway rewards records waiting to be withdrawn by `rewards_address`
+pub fn query_rewards_records(
+    querier: &QuerierWrapper<BindingQuery>,
+    rewards_address: String,
+    limit: u64,
+) -> StdResult<Vec<RewardsRecord>> {
+    let request = QueryRequest::Custom(BindingQuery::RewardsRecords {
+        rewards_address,
+        pagination: Some(PageRequest {
+            limit: Some(limit),
+            ..PageRequest::default()
+        }),
+    });
+    let res: RewardsRecordsResponse = querier.query(&request)?;
+    Ok(res.records)
+}
@@ -1,116 +1,329 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coin, coins, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut, 
-    DistributionMsg, Env, MessageInfo, QuerierWrapper, QueryRequest, WasmQuery,
-    Response, StakingMsg, StdError, StdResult, Uint128, WasmMsg
+    coin, coins, from_binary, to_binary, Addr, Timestamp, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut, 
+    DistributionMsg, Env, Event, GovMsg, MessageInfo, VoteOption, Order, QuerierWrapper, QueryRequest, WasmQuery, Reply,
+    Response, StakingMsg, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg
 };
 
-use cw2::set_contract_version;
-use cw20::{BalanceResponse, Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg, 
-    TokenInfoResponse, Cw20QueryMsg};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20ReceiveMsg, 
+    TokenInfoResponse, Cw20QueryMsg, MinterResponse};
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, parse_reply_instantiate_data};
 
-use crate::linked_list::{LinkedList, NodeWithId, node_update_value, linked_list, linked_list_read,
-    linked_list_append, linked_list_remove_head, linked_list_get_list};
+use crate::bindings::{query_full_denom, query_rewards_records, BindingMsg, BindingQuery};
+use crate::delegation::{get_delegations, split_delegation, split_undelegation, validate_epoch_period, validate_validators,
+    redelegatable, redelegation_entries, record_redelegation, rebalance_moves, MAX_REDELEGATION_ENTRIES};
+use crate::linked_list::{LinkedList, NodeWithId, LINKED_LIST, node_update_value,
+    linked_list_append, linked_list_remove, linked_list_remove_head, linked_list_get_list, NODES, RECEIVER_NODES};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, ConfigResponse, StatusResponse, UnstakingQueueResponse, 
-    InstantiateMsg, QueryMsg};
-use crate::state::{ConfigInfo, Supply, CONFIG, TOTAL_SUPPLY, CLAIMABLE, UNDER_UNSTAKING};
+use crate::migration::migrate_from_v0_1;
+use crate::slashing::{in_flight_undelegations, socialize_loss, SLASHING_DUST};
+use crate::rewards::{add_rewards, checkpoint, pending_rewards};
+use crate::governance::{apply_validator_changes, proposal_status, validate_validator_gov, weighted_options, ProposalStatus, Tally};
+use crate::msg::{ExecuteMsg, LiquidTokenInfo, MigrateMsg, ReceiveMsg, TokenInstantiateMsg, ConfigResponse, StatusResponse, UnstakingQueueResponse, 
+    InstantiateMsg, QueryMsg, PendingUnbonding, PendingUnbondingsResponse, RatioHistoryResponse, EstimatedAprResponse,
+    SimulateStakeResponse, SimulateUnstakeResponse, RewardsResponse, UnstakingRequestsResponse, ProposalTallyResponse, VoteResponse,
+    VotingPowerResponse, ValidatorProposalResponse, ValidatorProposalsResponse};
+use crate::state::{ConfigInfo, Epoch, QueueMode, RatioSnapshot, RewardMode, OwnershipProposal, PauseInfo, PendingMint, PendingProcess, Supply, Undelegation, ValidatorInfo, PendingInstantUnstake, CONFIG, TOTAL_SUPPLY, CLAIMABLE,
+    UNDER_UNSTAKING, CURRENT_EPOCH, UNDELEGATIONS, OWNERSHIP_PROPOSAL, PENDING_PROCESS, PENDING_INSTANT_UNSTAKE, LAST_HARVEST, RATIO_SNAPSHOTS, VOTES,
+    Ballot, ValidatorChange, ValidatorGovConfig, ValidatorProposal, ValidatorVote, VOTING_POWER, TOTAL_VOTING_POWER,
+    VALIDATOR_PROPOSAL_COUNT, VALIDATOR_PROPOSALS, VALIDATOR_VOTES, HOLDER_REWARDS, REWARD_INDEX, GovProposal, GovVote,
+    GOV_PROPOSALS, OPEN_GOV_PROPOSALS};
 
 const FALLBACK_RATIO: Decimal = Decimal::one();
+// protocol fee is expressed in basis points
+const FEE_DENOMINATOR: u64 = 10000;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:liquid-staking";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// pagination of the ratio history
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+// pagination of the unstaking queue
+const DEFAULT_QUEUE_LIMIT: u32 = 50;
+const MAX_QUEUE_LIMIT: u32 = 100;
+// number of queued requests paid out at most each time the queue is processed
+const PROCESS_QUEUE_LIMIT: u64 = 50;
+// number of governance proposals open to the votes of the holders at once, their votes are refreshed
+// every time the liquid tokens of a voter move
+const MAX_OPEN_GOV_PROPOSALS: u64 = 10;
+// number of Archway rewards records withdrawn at most at once
+const ARCHWAY_RECORDS_LIMIT: u64 = 100;
+const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+const PROCESS_TOKEN_REPLY_ID: u64 = 2;
+const INSTANT_UNSTAKE_REPLY_ID: u64 = 3;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
+    deps: DepsMut<BindingQuery>,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let linked_list_init = LinkedList {
         head_id: 0,
         tail_id: 0,
-        length: 0
+        length: 0,
+        last_id: 0,
     };
-    linked_list(deps.storage).save(&linked_list_init)?;
+    LINKED_LIST.save(deps.storage, &linked_list_init)?;
 
+    validate_validators(&deps.querier, &msg.validators)?;
+    validate_epoch_period(msg.epoch_period, msg.unbonding_period)?;
+    if msg.protocol_fee > FEE_DENOMINATOR {
+        return Err(ContractError::InvalidProtocolFee { max: FEE_DENOMINATOR });
+    }
+    if msg.protocol_fee + msg.harvest_bounty > FEE_DENOMINATOR {
+        return Err(ContractError::InvalidHarvestBounty { max: FEE_DENOMINATOR });
+    }
+    if let Some(validator_gov) = &msg.validator_gov {
+        validate_validator_gov(validator_gov)?;
+    }
     let denom = deps.querier.query_bonded_denom()?;
-    let config_init = ConfigInfo {
+    let mut config_init = ConfigInfo {
         owner: info.sender,
         bond_denom: denom,
-        liquid_token_addr: Addr::unchecked("none"), // msg.liquid_token_addr,
-        validator: msg.validator,
+        // set once the liquid token is instantiated, see reply_instantiate_token
+        liquid_token_addr: Addr::unchecked(""),
+        liquid_denom: None,
+        validators: msg.validators,
+        unbonding_period: msg.unbonding_period,
+        rebalance_threshold: msg.rebalance_threshold,
+        epoch_period: msg.epoch_period,
+        protocol_fee: msg.protocol_fee,
+        treasury: deps.api.addr_validate(&msg.treasury)?,
+        harvest_bounty: msg.harvest_bounty,
+        harvest_interval: msg.harvest_interval,
+        max_tvl: msg.max_tvl,
+        min_stake: msg.min_stake,
+        min_unstake: msg.min_unstake,
+        max_stake_per_address: msg.max_stake_per_address,
+        swap_contract: msg.swap_contract.map(|swap_contract| deps.api.addr_validate(&swap_contract)).transpose()?,
+        guardian: msg.guardian.map(|guardian| deps.api.addr_validate(&guardian)).transpose()?,
+        paused: PauseInfo::default(),
+        validator_gov: msg.validator_gov,
+        queue_mode: msg.queue_mode,
+        reward_mode: msg.reward_mode,
     };
-    CONFIG.save(deps.storage, &config_init)?;
+
+    let epoch_init = Epoch {
+        id: 0,
+        start: env.block.time,
+        pending: Uint128::zero(),
+    };
+    CURRENT_EPOCH.save(deps.storage, &epoch_init)?;
 
     // set supply to 0
     let supply_init = Supply::default();
     TOTAL_SUPPLY.save(deps.storage, &supply_init)?;
 
-    Ok(Response::default())
+    let res = match msg.liquid_token {
+        LiquidTokenInfo::Cw20 { code_id, name, symbol, decimals } => {
+            // instantiate the liquid token with this contract as the only minter
+            let token_msg = TokenInstantiateMsg {
+                name,
+                symbol: symbol.clone(),
+                decimals,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            };
+            Response::new().add_submessage(SubMsg::reply_on_success(
+                WasmMsg::Instantiate {
+                    admin: Some(config_init.owner.to_string()),
+                    code_id,
+                    msg: to_binary(&token_msg)?,
+                    funds: vec![],
+                    label: format!("{} liquid token", symbol),
+                },
+                INSTANTIATE_TOKEN_REPLY_ID,
+            ))
+        }
+        LiquidTokenInfo::TokenFactory { subdenom } => {
+            // the denom is owned by this contract, only it can mint and burn
+            let liquid_denom = query_full_denom(&deps.querier, env.contract.address.to_string(), subdenom.clone())?;
+            config_init.liquid_denom = Some(liquid_denom.clone());
+            Response::new()
+                .add_message(BindingMsg::CreateDenom { subdenom })
+                .add_attribute("liquid_denom", liquid_denom)
+        }
+    };
+    CONFIG.save(deps.storage, &config_init)?;
+
+    Ok(res.add_attribute("action", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut<BindingQuery>, env: Env, msg: Reply) -> Result<Response<BindingMsg>, ContractError> {
+    match msg.id {
+        INSTANTIATE_TOKEN_REPLY_ID => reply_instantiate_token(deps, msg),
+        PROCESS_TOKEN_REPLY_ID => reply_process_token(deps, env),
+        INSTANT_UNSTAKE_REPLY_ID => reply_instant_unstake(deps, env),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+// store the address of the liquid token instantiated by this contract
+pub fn reply_instantiate_token(deps: DepsMut<BindingQuery>, msg: Reply) -> Result<Response<BindingMsg>, ContractError> {
+    let res = parse_reply_instantiate_data(msg)?;
+    let address = deps.api.addr_validate(&res.contract_address)?;
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        config.liquid_token_addr = address.clone();
+        Ok(config)
+    })?;
+
+    let res = Response::new()
+        .add_attribute("action", "setLiquidToken")
+        .add_attribute("address", address);
+    Ok(res)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     match msg {
-        ExecuteMsg::Stake {} => execute_stake(deps, env, info),
-        ExecuteMsg::Claim {} => execute_claim(deps, info),
-        ExecuteMsg::SetLiquidToken { address } => execute_set_liquid_token(deps, info, address),
+        ExecuteMsg::Stake { recipient } => execute_stake(deps, env, info, recipient),
+        ExecuteMsg::Claim { recipient } => execute_claim(deps, info, recipient),
+        ExecuteMsg::Unstake { recipient } => execute_unstake_denom(deps, env, info, recipient),
+        ExecuteMsg::CancelUnstake { request_id } => execute_cancel_unstake(deps, env, info, request_id),
+        ExecuteMsg::WithdrawArchwayRewards {} => execute_withdraw_archway_rewards(deps, env, info),
+        ExecuteMsg::Harvest {} => execute_harvest(deps, env, info),
+        ExecuteMsg::ClaimRewards {} => execute_claim_rewards(deps, info),
+        ExecuteMsg::UpdateIndex {} => execute_update_index(deps, info),
+        ExecuteMsg::Vote { proposal_id, option } => execute_vote(deps, env, info, proposal_id, option),
+        ExecuteMsg::CastVote { proposal_id } => execute_cast_vote(deps, env, info, proposal_id),
+        ExecuteMsg::LockVotingPower {} => execute_lock_voting_power_denom(deps, env, info),
+        ExecuteMsg::UnlockVotingPower { amount } => execute_unlock_voting_power(deps, env, info, amount),
+        ExecuteMsg::ProposeValidatorChanges { changes } => execute_propose_validator_changes(deps, env, info, changes),
+        ExecuteMsg::VoteValidatorProposal { proposal_id, ballot } => 
+            execute_vote_validator_proposal(deps, env, info, proposal_id, ballot),
+        ExecuteMsg::ExecuteValidatorProposal { proposal_id } => execute_validator_proposal(deps, env, info, proposal_id),
+        ExecuteMsg::UpdateValidators { validators } => execute_update_validators(deps, info, validators),
+        ExecuteMsg::Redelegate { src, dst, amount } => execute_redelegate(deps, env, info, src, dst, amount),
+        ExecuteMsg::SetRebalanceThreshold { threshold } => execute_set_rebalance_threshold(deps, info, threshold),
+        ExecuteMsg::SetProtocolFee { protocol_fee, treasury } => execute_set_protocol_fee(deps, info, protocol_fee, treasury),
+        ExecuteMsg::SetHarvestBounty { harvest_bounty, harvest_interval } => 
+            execute_set_harvest_bounty(deps, info, harvest_bounty, harvest_interval),
+        ExecuteMsg::SetStakeLimits { max_tvl, min_stake, min_unstake, max_stake_per_address } => 
+            execute_set_stake_limits(deps, info, max_tvl, min_stake, min_unstake, max_stake_per_address),
+        ExecuteMsg::SetSwapContract { swap_contract } => execute_set_swap_contract(deps, info, swap_contract),
+        ExecuteMsg::RegisterProposal { proposal_id, voting_end } => 
+            execute_register_proposal(deps, env, info, proposal_id, voting_end),
+        ExecuteMsg::SetValidatorGov { validator_gov } => execute_set_validator_gov(deps, info, validator_gov),
+        ExecuteMsg::SetGuardian { guardian } => execute_set_guardian(deps, info, guardian),
+        ExecuteMsg::Pause { stake, unstake, claim } => execute_pause(deps, info, stake, unstake, claim),
+        ExecuteMsg::Unpause { stake, unstake, claim } => execute_unpause(deps, info, stake, unstake, claim),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => execute_propose_new_owner(deps, env, info, owner, expires_in),
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => execute_cancel_ownership_proposal(deps, info),
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
-        ExecuteMsg::_ProcessToken { balance_before } => _process_token(deps, env, info, balance_before),
-        ExecuteMsg::_PerformCheck {} => _perform_check(deps, env, info),
-        ExecuteMsg::_MintLiquidToken { receiver, native_amount } => _mint_liquid_token(deps, env, info, receiver, native_amount),
     }
 }
 
 // process unstaking queue then stake remain available native token
-pub fn _process_token(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    balance_before: Uint128,
-) -> Result<Response, ContractError> {
-    // only allow this contract to call itself
-    if info.sender != env.contract.address {
-        return Err(ContractError::Unauthorized {});
-    }
-
+pub fn process_token(
+    deps: DepsMut<BindingQuery>,
+    env: &Env,
+    pending: &PendingProcess,
+) -> Result<Response<BindingMsg>, ContractError> {
     let zero_balance = Uint128::zero();
     // check how many available native token we have
     let config = CONFIG.load(deps.storage)?;
     let mut balance = deps
         .querier
         .query_balance(&env.contract.address, &config.bond_denom)?;
-    let claimed_reward = balance.amount.checked_sub(balance_before).map_err(StdError::overflow)?;
+    let claimed_reward = balance.amount.checked_sub(pending.balance_before).map_err(StdError::overflow)?;
 
+    // take the protocol fee and the harvest bounty out of the rewards, the rest goes to liquid token holders
+    let protocol_fee = claimed_reward.multiply_ratio(config.protocol_fee, FEE_DENOMINATOR);
+    let harvest_bounty = match pending.harvester {
+        Some(_) => claimed_reward.multiply_ratio(config.harvest_bounty, FEE_DENOMINATOR),
+        None => Uint128::zero(),
+    };
     let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
-    supply.native += claimed_reward;
-    balance.amount = balance.amount.checked_sub(supply.claims).map_err(StdError::overflow)?;
-    // process unstaking queue
-    let unstaking_requests: Vec<NodeWithId> = linked_list_get_list(deps.storage, 50)?;
+    let issued = get_token_supply(&deps.querier, &config, &supply)?
+        .checked_sub(pending.burning)
+        .map_err(StdError::overflow)?;
+    let reward = claimed_reward
+        .checked_sub(protocol_fee + harvest_bounty)
+        .map_err(StdError::overflow)?;
+    match config.reward_mode {
+        // kept apart for the holders of locked liquid tokens to claim
+        RewardMode::Index => {
+            let locked = TOTAL_VOTING_POWER.may_load(deps.storage)?.unwrap_or_default();
+            add_rewards(deps.storage, &mut supply, reward, locked)?;
+        }
+        RewardMode::Compound => supply.native += reward,
+    }
+    supply.fees += protocol_fee;
+
+    // reconcile the supply with the native tokens the contract really holds,
+    // a shortfall means a validator was slashed
+    let bonded = get_bonded(&deps.querier, &env.contract.address)?;
+    let in_flight = in_flight_undelegations(deps.storage, env.block.time, env.block.height)?;
+    // tokens staked by the pending mint are in the balance but not in the supply yet
+    let minting = pending.mint.as_ref().map(|mint| mint.native_amount).unwrap_or_default();
+    let held = bonded + balance.amount + in_flight;
+    let owed = supply.native.saturating_sub(supply.peg_shortfall) + supply.unstakings + supply.claims + supply.rewards + protocol_fee + harvest_bounty + minting;
+    let mut slashing_event = None;
+    if held + SLASHING_DUST < owed {
+        let loss = owed - held;
+        let unstaking_loss = socialize_loss(&mut supply, loss, config.reward_mode == RewardMode::Index)?;
+        slashing_event = Some(
+            Event::new("slashing_detected")
+                .add_attribute("loss", loss)
+                .add_attribute("native_loss", loss - unstaking_loss)
+                .add_attribute("unstaking_loss", unstaking_loss),
+        );
+    }
+    balance.amount = balance.amount.checked_sub(supply.claims + supply.rewards).map_err(StdError::overflow)?;
+    balance.amount = balance.amount.checked_sub(protocol_fee + harvest_bounty).map_err(StdError::overflow)?;
+    // process unstaking queue, a request is only paid once the undelegation of its epoch matured
+    let unstaking_requests: Vec<NodeWithId> = linked_list_get_list(deps.storage, None, PROCESS_QUEUE_LIMIT)?;
     for request in unstaking_requests {
         if balance.amount == zero_balance {
             break;
         }
-        let payout: Uint128;
-        if request.info.value <= balance.amount {
-            payout = request.info.value;
+        let undelegation = match UNDELEGATIONS.may_load(deps.storage, request.info.epoch)? {
+            Some(undelegation) if undelegation.completion <= env.block.time => undelegation,
+            _ => break,
+        };
+        // `paid` is the part of the request paid out in the unit of the queue, `paid_units` its units
+        let units = epoch_units(&config, &undelegation, request.info.value);
+        let value = units_to_native(&supply, units);
+        let (payout, paid, paid_units) = if value <= balance.amount {
             linked_list_remove_head(deps.storage)?;
+            (value, request.info.value, units)
         } else {
-            payout = balance.amount;
-            node_update_value(deps.storage, request.id, request.info.value.checked_sub(payout).map_err(StdError::overflow)?)?;
-        }
+            let paid_units = native_to_units(&supply, balance.amount);
+            let paid = match config.queue_mode {
+                QueueMode::Native => paid_units,
+                QueueMode::Liquid => paid_units.multiply_ratio(undelegation.liquid, undelegation.units),
+            };
+            if paid.is_zero() {
+                break;
+            }
+            node_update_value(deps.storage, request.id, request.info.value.checked_sub(paid).map_err(StdError::overflow)?)?;
+            let paid_units = epoch_units(&config, &undelegation, paid);
+            (units_to_native(&supply, paid_units), paid, paid_units)
+        };
         supply.unstakings = supply.unstakings.checked_sub(payout).map_err(StdError::overflow)?;
+        supply.unstaking_units = supply.unstaking_units.checked_sub(paid_units).map_err(StdError::overflow)?;
         balance.amount = balance.amount.checked_sub(payout).map_err(StdError::overflow)?;
         supply.claims += payout;
         CLAIMABLE.update(
@@ -121,70 +334,209 @@ pub fn _process_token(
         UNDER_UNSTAKING.update(
             deps.storage,
             &request.info.receiver,
-            |unstaking: Option<Uint128>| -> StdResult<_> { Ok(unstaking.unwrap_or_default().checked_sub(payout)?) },
+            |unstaking: Option<Uint128>| -> StdResult<_> { Ok(unstaking.unwrap_or_default().checked_sub(paid)?) },
         )?;
     }
+    let mut event = Event::new("process_token")
+        .add_attribute("reward", claimed_reward)
+        .add_attribute("protocol_fee", protocol_fee);
     let mut res = Response::new();
-    // and bond remain available to the validator
-    if supply.unstakings == zero_balance && balance.amount > zero_balance{
-        res = res.add_message(StakingMsg::Delegate {
-            validator: config.validator,
-            amount: balance.clone(),
-        })
-    } else if supply.unstakings > zero_balance && balance.amount == zero_balance {
-        // unbond if not enough available native token to process unstaking
-        let bonded = get_bonded(&deps.querier, &env.contract.address)?;
-        if bonded > supply.native {
-            let unstake_amount = bonded.checked_sub(supply.native).map_err(StdError::overflow)?;
+    if !protocol_fee.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: config.treasury.to_string(),
+            amount: coins(protocol_fee.u128(), &config.bond_denom),
+        });
+    }
+    if let Some(harvester) = &pending.harvester {
+        event = event
+            .add_attribute("harvester", harvester.clone())
+            .add_attribute("harvest_bounty", harvest_bounty);
+        if !harvest_bounty.is_zero() {
+            res = res.add_message(BankMsg::Send {
+                to_address: harvester.to_string(),
+                amount: coins(harvest_bounty.u128(), &config.bond_denom),
+            });
+        }
+    }
+    // bond remain available to the validators, queued requests are funded by their epoch's undelegation.
+    // Requests of matured epochs left past the limit are paid by the next process out of what remains
+    let matured_left = match linked_list_get_list(deps.storage, None, 1)?.first() {
+        Some(head) => UNDELEGATIONS
+            .may_load(deps.storage, head.info.epoch)?
+            .is_some_and(|undelegation| undelegation.completion <= env.block.time),
+        None => false,
+    };
+    if balance.amount > zero_balance && !matured_left {
+        let delegations = get_delegations(&deps.querier, &env.contract.address)?;
+        for (validator, amount) in split_delegation(&config.validators, &delegations, balance.amount) {
+            res = res.add_message(StakingMsg::Delegate {
+                validator,
+                amount: coin(amount.u128(), &config.bond_denom),
+            })
+        }
+    }
+    // unbond the requests gathered in the current epoch,
+    // at most once per epoch to stay under the chain's unbonding entry limit
+    let epoch = CURRENT_EPOCH.load(deps.storage)?;
+    if env.block.time >= epoch.start.plus_seconds(config.epoch_period) && epoch.pending > zero_balance {
+        let (pending, units) = match config.queue_mode {
+            QueueMode::Native => (units_to_native(&supply, epoch.pending), epoch.pending),
+            // the liquid requests leave the exchange rate for the unstaking queue at their current value
+            QueueMode::Liquid => {
+                let pending = liquid_to_native(epoch.pending, supply.native, issued + supply.unstaking_liquid);
+                supply.native = supply.native.checked_sub(pending).map_err(StdError::overflow)?;
+                supply.unstaking_liquid = supply.unstaking_liquid.checked_sub(epoch.pending).map_err(StdError::overflow)?;
+                let units = native_to_units(&supply, pending);
+                supply.unstakings += pending;
+                supply.unstaking_units += units;
+                (pending, units)
+            }
+        };
+        let delegations = get_delegations(&deps.querier, &env.contract.address)?;
+        let mut unstake_amount = Uint128::zero();
+        for (validator, amount) in split_undelegation(&config.validators, &delegations, pending) {
+            unstake_amount += amount;
             res = res.add_message(StakingMsg::Undelegate {
-                validator: config.validator,
-                amount: coin(unstake_amount.u128(), &config.bond_denom),
+                validator,
+                amount: coin(amount.u128(), &config.bond_denom),
             })
         }
+        let undelegation = Undelegation {
+            amount: unstake_amount,
+            time: env.block.time,
+            completion: env.block.time.plus_seconds(config.unbonding_period),
+            matured_at: None,
+            liquid: match config.queue_mode {
+                QueueMode::Native => Uint128::zero(),
+                QueueMode::Liquid => epoch.pending,
+            },
+            units,
+        };
+        UNDELEGATIONS.save(deps.storage, epoch.id, &undelegation)?;
+        // requests queued from now on are funded by the next epoch's undelegation
+        let next_epoch = Epoch {
+            id: epoch.id + 1,
+            start: env.block.time,
+            pending: Uint128::zero(),
+        };
+        CURRENT_EPOCH.save(deps.storage, &next_epoch)?;
+        event = event
+            .add_attribute("epoch", epoch.id.to_string())
+            .add_attribute("undelegated", unstake_amount);
     }
     TOTAL_SUPPLY.save(deps.storage, &supply)?;
 
-    res = res
-        .add_attribute("action", "_processToken")
-        .add_attribute("bonded", balance.amount);
+    // keep a snapshot of the exchange rate when a harvest compounded the rewards,
+    // stake and unstake run this too but must not grow the snapshots at will
+    if pending.harvester.is_some() {
+        save_ratio_snapshot(deps.storage, env, &supply, issued + supply.unstaking_liquid)?;
+    }
+
+    event = event.add_attribute("bonded", balance.amount);
+    res = res.add_event(event);
+    if let Some(slashing_event) = slashing_event {
+        res = res.add_event(slashing_event);
+    }
     Ok(res)
 }
 
-// claim staking reward, process withdraw queue, then stake available native token
-pub fn _perform_check(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-    // only allow this contract to call itself
-    if info.sender != env.contract.address {
-        return Err(ContractError::Unauthorized {});
-    }
-    
+// claim staking reward, then process withdraw queue and stake available native token
+// once the rewards arrived, minting liquid tokens for `mint` afterwards if any.
+// `harvester` is paid the harvest bounty out of the claimed rewards
+pub fn perform_check(
+    deps: DepsMut<BindingQuery>,
+    env: &Env,
+    mint: Option<PendingMint>,
+    harvester: Option<Addr>,
+    burning: Uint128,
+) -> Result<Response<BindingMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let balance_before = deps
         .querier
         .query_balance(&env.contract.address, &config.bond_denom)?.amount;
-    // claim reward then process available native token
-    let mut res = Response::new();
-    // claim staking rewards
-    let bonded = get_bonded(&deps.querier, &env.contract.address)?;
-    if bonded > Uint128::zero() {
-        res = res.add_message(DistributionMsg::WithdrawDelegatorReward {
-            validator: config.validator,
-        })
+    let mut msgs: Vec<CosmosMsg<BindingMsg>> = vec![];
+    // claim staking rewards from every validator we are delegating to, before any redelegation
+    // moves a whole delegation away
+    for (validator, _) in get_delegations(&deps.querier, &env.contract.address)? {
+        msgs.push(DistributionMsg::WithdrawDelegatorReward { validator }.into())
+    }
+    // move stake back toward the target weights if it drifted too far
+    let moves = rebalance_moves(
+        deps.storage,
+        &deps.querier,
+        &env.contract.address,
+        &config.validators,
+        config.rebalance_threshold,
+        env.block.time,
+    )?;
+    let completion = env.block.time.plus_seconds(config.unbonding_period);
+    for (src, dst, amount) in moves {
+        record_redelegation(deps.storage, &src, &dst, env.block.time, completion)?;
+        msgs.push(StakingMsg::Redelegate {
+            src_validator: src,
+            dst_validator: dst,
+            amount: coin(amount.u128(), &config.bond_denom),
+        }.into())
+    }
+    let event = Event::new("perform_check").add_attribute("balance", balance_before);
+    let mut pending = PendingProcess { balance_before, mint, harvester, burning: Uint128::zero() };
+
+    // nothing to wait for, process available native token right away
+    let last_msg = match msgs.pop() {
+        Some(msg) => msg,
+        None => {
+            pending.burning = burning;
+            return Ok(finish_process(deps, env, pending)?.add_event(event));
+        }
+    };
+    // otherwise process it in reply, once the last message went through
+    PENDING_PROCESS.save(deps.storage, &pending)?;
+    let res = Response::new()
+        .add_messages(msgs)
+        .add_submessage(SubMsg::reply_on_success(last_msg, PROCESS_TOKEN_REPLY_ID))
+        .add_event(event);
+    Ok(res)
+}
+
+// process available native token after staking rewards were claimed by perform_check
+pub fn reply_process_token(deps: DepsMut<BindingQuery>, env: Env) -> Result<Response<BindingMsg>, ContractError> {
+    let pending = PENDING_PROCESS.load(deps.storage)?;
+    PENDING_PROCESS.remove(deps.storage);
+    finish_process(deps, &env, pending)
+}
+
+// process available native token, then mint the pending liquid tokens if any
+fn finish_process(mut deps: DepsMut<BindingQuery>, env: &Env, pending: PendingProcess) -> Result<Response<BindingMsg>, ContractError> {
+    let mut res = process_token(deps.branch(), env, &pending)?;
+    if let Some(mint) = pending.mint {
+        res = merge_response(res, mint_liquid_token(deps, mint)?);
     }
-    // process unstaking queue and available native token
-    let msg = to_binary(&ExecuteMsg::_ProcessToken { balance_before })?;
-    res = res.add_message(WasmMsg::Execute {
-            contract_addr: env.contract.address.to_string(),
-            msg,
-            funds: vec![],
-        })
-        .add_attribute("action", "_performCheck")
-        .add_attribute("balance", balance_before);
     Ok(res)
 }
 
+// save_ratio_snapshot records the exchange rate of `supply` at the current block
+fn save_ratio_snapshot(storage: &mut dyn Storage, env: &Env, supply: &Supply, issued: Uint128) -> StdResult<()> {
+    if issued.is_zero() {
+        return Ok(());
+    }
+    let snapshot = RatioSnapshot {
+        height: env.block.height,
+        time: env.block.time,
+        native: supply.native,
+        issued,
+        ratio: Decimal::from_ratio(supply.native, issued),
+    };
+    RATIO_SNAPSHOTS.save(storage, env.block.height, &snapshot)
+}
+
+// merge_response appends the messages and events of `other` to `res`
+fn merge_response(res: Response<BindingMsg>, other: Response<BindingMsg>) -> Response<BindingMsg> {
+    res.add_submessages(other.messages).add_events(other.events)
+}
+
 // get_bonded returns the total amount of delegations from contract
 // it ensures they are all the same denom
-fn get_bonded(querier: &QuerierWrapper, contract: &Addr) -> Result<Uint128, ContractError> {
+fn get_bonded(querier: &QuerierWrapper<BindingQuery>, contract: &Addr) -> Result<Uint128, ContractError> {
     let bonds = querier.query_all_delegations(contract)?;
     if bonds.is_empty() {
         return Ok(Uint128::zero());
@@ -203,63 +555,199 @@ fn get_bonded(querier: &QuerierWrapper, contract: &Addr) -> Result<Uint128, Cont
     })
 }
 
-fn get_token_supply(querier: &QuerierWrapper, token_addr: Addr,) -> StdResult<Uint128> {
+// get_token_supply returns how many liquid tokens are issued, the token-factory denom supply is tracked by the contract
+fn get_token_supply(querier: &QuerierWrapper<BindingQuery>, config: &ConfigInfo, supply: &Supply) -> StdResult<Uint128> {
+    if config.liquid_denom.is_some() {
+        return Ok(supply.issued);
+    }
     let cw20_query_response: TokenInfoResponse =
        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: token_addr.to_string(),
+            contract_addr: config.liquid_token_addr.to_string(),
             msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
     }))?;
 
     Ok(cw20_query_response.total_supply)
  }
 
-// mint new liquid token to native token sender
-pub fn _mint_liquid_token(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    receiver: Addr,
-    native_amount: Uint128,
-) -> Result<Response, ContractError> {
-    // only allow this contract to call itself
-    if info.sender != env.contract.address {
-        return Err(ContractError::Unauthorized {});
+// get_liquid_shares returns how many liquid tokens share the native tokens of the supply: the issued ones
+// and, in liquid queue mode, the ones burned by the requests waiting in the unstaking queue
+fn get_liquid_shares(querier: &QuerierWrapper<BindingQuery>, config: &ConfigInfo, supply: &Supply) -> StdResult<Uint128> {
+    Ok(get_token_supply(querier, config, supply)? + supply.unstaking_liquid)
+}
+
+// units_to_native returns the native tokens `units` of the native unstaking queue are worth
+fn units_to_native(supply: &Supply, units: Uint128) -> Uint128 {
+    if supply.unstaking_units.is_zero() {
+        units
+    } else {
+        units.multiply_ratio(supply.unstakings, supply.unstaking_units)
+    }
+}
+
+// native_to_units returns the units of the native unstaking queue `native_amount` native tokens are worth
+fn native_to_units(supply: &Supply, native_amount: Uint128) -> Uint128 {
+    if supply.unstaking_units.is_zero() || supply.unstakings.is_zero() {
+        native_amount
+    } else {
+        native_amount.multiply_ratio(supply.unstaking_units, supply.unstakings)
+    }
+}
+
+// epoch_units returns the units of the unstaking queue held by `value` of a request of an undelegated epoch
+fn epoch_units(config: &ConfigInfo, undelegation: &Undelegation, value: Uint128) -> Uint128 {
+    match config.queue_mode {
+        QueueMode::Native => value,
+        QueueMode::Liquid if undelegation.liquid.is_zero() => Uint128::zero(),
+        QueueMode::Liquid => value.multiply_ratio(undelegation.units, undelegation.liquid),
     }
+}
+
+// queued_native returns the native tokens `value` of a request queued in `epoch` is worth now. Liquid requests
+// follow the exchange rate until their epoch is undelegated, then share the unstaking queue like native ones
+fn queued_native(
+    storage: &dyn Storage,
+    config: &ConfigInfo,
+    supply: &Supply,
+    shares: Uint128,
+    epoch: u64,
+    value: Uint128,
+) -> StdResult<Uint128> {
+    let native_amount = match (&config.queue_mode, UNDELEGATIONS.may_load(storage, epoch)?) {
+        (QueueMode::Native, _) => units_to_native(supply, value),
+        (QueueMode::Liquid, Some(undelegation)) => units_to_native(supply, epoch_units(config, &undelegation, value)),
+        (QueueMode::Liquid, None) if value.is_zero() => value,
+        (QueueMode::Liquid, None) => liquid_to_native(value, supply.native, shares),
+    };
+    Ok(native_amount)
+}
+
+fn get_token_balance(querier: &QuerierWrapper<BindingQuery>, config: &ConfigInfo, address: &Addr) -> StdResult<Uint128> {
+    if let Some(liquid_denom) = &config.liquid_denom {
+        return Ok(querier.query_balance(address, liquid_denom)?.amount);
+    }
+    let cw20_query_response: BalanceResponse =
+        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: config.liquid_token_addr.to_string(),
+            msg: to_binary(&Cw20QueryMsg::Balance { address: address.to_string() })?,
+        }))?;
+
+    Ok(cw20_query_response.balance)
+}
+
+// transfer_msg sends `amount` liquid tokens held by this contract to `recipient`
+fn transfer_msg(config: &ConfigInfo, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg<BindingMsg>> {
+    let msg = match &config.liquid_denom {
+        Some(liquid_denom) => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), liquid_denom),
+        }
+        .into(),
+        None => WasmMsg::Execute {
+            contract_addr: config.liquid_token_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: recipient.to_string(), amount })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+    Ok(msg)
+}
+
+// mint_msg mints `amount` liquid tokens to `recipient`
+fn mint_msg(config: &ConfigInfo, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg<BindingMsg>> {
+    let msg = match &config.liquid_denom {
+        Some(liquid_denom) => BindingMsg::MintTokens {
+            denom: liquid_denom.clone(),
+            amount,
+            mint_to_address: recipient.to_string(),
+        }.into(),
+        None => WasmMsg::Execute {
+            contract_addr: config.liquid_token_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint { recipient: recipient.to_string(), amount })?,
+            funds: vec![],
+        }.into(),
+    };
+    Ok(msg)
+}
+
+// burn_msg burns `amount` liquid tokens held by this contract
+fn burn_msg(config: &ConfigInfo, contract: &Addr, amount: Uint128) -> StdResult<CosmosMsg<BindingMsg>> {
+    let msg = match &config.liquid_denom {
+        Some(liquid_denom) => BindingMsg::BurnTokens {
+            denom: liquid_denom.clone(),
+            amount,
+            burn_from_address: contract.to_string(),
+        }.into(),
+        None => WasmMsg::Execute {
+            contract_addr: config.liquid_token_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }.into(),
+    };
+    Ok(msg)
+}
+
+// native_to_liquid returns the liquid tokens minted for `native_amount` native tokens
+fn native_to_liquid(native_amount: Uint128, native: Uint128, liquid_supply: Uint128) -> Uint128 {
+    if liquid_supply.is_zero() {
+        FALLBACK_RATIO * native_amount
+    } else {
+        native_amount.multiply_ratio(liquid_supply, native)
+    }
+}
+
+// liquid_to_native returns the native tokens `liquid_amount` liquid tokens are worth
+fn liquid_to_native(liquid_amount: Uint128, native: Uint128, liquid_supply: Uint128) -> Uint128 {
+    liquid_amount.multiply_ratio(native, liquid_supply)
+}
+
+// estimate_release_time returns when the requests queued in the current epoch become claimable,
+// the epoch is undelegated once it's over
+fn estimate_release_time(config: &ConfigInfo, epoch: &Epoch, now: Timestamp) -> Timestamp {
+    let undelegation_time = epoch.start.plus_seconds(config.epoch_period).max(now);
+    undelegation_time.plus_seconds(config.unbonding_period)
+}
 
+// mint new liquid token to native token sender
+pub fn mint_liquid_token(deps: DepsMut<BindingQuery>, mint: PendingMint) -> Result<Response<BindingMsg>, ContractError> {
+    let PendingMint { receiver, native_amount } = mint;
     let config = CONFIG.load(deps.storage)?;
     // calculate to_mint and update total supply
     let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
-    let liquid_supply = get_token_supply(&deps.querier, config.liquid_token_addr.clone())?;
-    let to_mint = if liquid_supply.is_zero() {
-        FALLBACK_RATIO * native_amount
-    } else {
-        native_amount.multiply_ratio(liquid_supply, supply.native)
-    };
+    let liquid_supply = get_liquid_shares(&deps.querier, &config, &supply)?;
+    let to_mint = native_to_liquid(native_amount, supply.native, liquid_supply);
     supply.native += native_amount;
+    if config.liquid_denom.is_some() {
+        supply.issued += to_mint;
+    }
     TOTAL_SUPPLY.save(deps.storage, &supply)?;
 
-    let mut res = Response::new()
-        .add_attribute("action", "_mintLiquidToken")
-        .add_attribute("from", receiver.clone())
-        .add_attribute("staked", native_amount)
-        .add_attribute("minted", to_mint);
-
-    // transfer cw20 liquid token to staker
-    // Cw20Contract is a function helper that provides several queries and message builder.
-    let cw20 = Cw20Contract(config.liquid_token_addr);
-    // Build a cw20 transfer send msg, that send collected funds to target address
-    let msg = cw20.call(Cw20ExecuteMsg::Mint {
-        recipient: receiver.into_string(),
-        amount: to_mint,
-    })?;
-    res = res.add_message(msg);
-    
+    let res = Response::new().add_event(
+        Event::new("mint_liquid_token")
+            .add_attribute("from", receiver.clone())
+            .add_attribute("staked", native_amount)
+            .add_attribute("minted", to_mint),
+    );
+
+    // mint liquid token to staker
+    let res = res.add_message(mint_msg(&config, &receiver, to_mint)?);
     Ok(res)
 }
 
-pub fn execute_stake(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn execute_stake(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response<BindingMsg>, ContractError> {
     // ensure we have the proper denom
     let config = CONFIG.load(deps.storage)?;
+    if config.paused.stake {
+        return Err(ContractError::Paused { operation: "stake".to_string() });
+    }
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
     // payment finds the proper coin (or throws an error)
     let payment = info
         .funds
@@ -268,75 +756,204 @@ pub fn execute_stake(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respo
         .ok_or_else(|| ContractError::EmptyBalance {
             denom: config.bond_denom.clone(),
         })?;
+    if payment.amount < config.min_stake {
+        return Err(ContractError::StakeBelowMinimum { min: config.min_stake });
+    }
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    if let Some(cap) = config.max_tvl {
+        if supply.native + payment.amount > cap {
+            return Err(ContractError::TvlCapExceeded { cap });
+        }
+    }
+    if let Some(limit) = config.max_stake_per_address {
+        // value of the liquid tokens already held or locked by the recipient
+        let liquid_supply = get_liquid_shares(&deps.querier, &config, &supply)?;
+        let held = if liquid_supply.is_zero() {
+            Uint128::zero()
+        } else {
+            let balance = get_token_balance(&deps.querier, &config, &recipient)?
+                + VOTING_POWER.may_load(deps.storage, &recipient)?.unwrap_or_default();
+            liquid_to_native(balance, supply.native, liquid_supply)
+        };
+        if held + payment.amount > limit {
+            return Err(ContractError::AddressStakeLimitExceeded { limit });
+        }
+    }
 
-    let contract_addr = env.contract.address;
-    let msg1 = to_binary(&ExecuteMsg::_PerformCheck {})?;
-    let msg2 = to_binary(&ExecuteMsg::_MintLiquidToken { receiver: info.sender, native_amount: payment.amount })?;
-    
-    let res = Response::new()
-        .add_message(WasmMsg::Execute {
-            contract_addr: contract_addr.to_string(),
-            msg: msg1,
-            funds: vec![],
-        })
-        .add_message(WasmMsg::Execute {
-            contract_addr: contract_addr.to_string(),
-            msg: msg2,
-            funds: vec![],
-        });
+    // liquid tokens are minted once staking rewards are compounded
+    let mint = PendingMint {
+        receiver: recipient.clone(),
+        native_amount: payment.amount,
+    };
+    let res = perform_check(deps, &env, Some(mint), None, Uint128::zero())?
+        .add_attribute("action", "stake")
+        .add_attribute("from", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", payment.amount);
     Ok(res)
 }
 
 pub fn execute_unstake(
-    deps: DepsMut,
+    mut deps: DepsMut<BindingQuery>,
     env: Env,
     sender: Addr,
+    recipient: Addr,
     amount: Uint128,
-) -> Result<Response, ContractError> {
-    let contract_addr = env.contract.address;
+) -> Result<Response<BindingMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     // burn liquid token
-    let cw20 = Cw20Contract(config.liquid_token_addr.clone());
-    // Build a cw20 transfer send msg, that send collected funds to target address
-    let msg1 = cw20.call(Cw20ExecuteMsg::Burn {
-        amount,
-    })?;
+    let msg1 = burn_msg(&config, &env.contract.address, amount)?;
 
     // put unstaker to unstaking queue, update info
     let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
-    let liquid_supply = get_token_supply(&deps.querier, config.liquid_token_addr)?;
-    let amount_to_unstake = amount.multiply_ratio(supply.native, liquid_supply);
-    supply.native = supply.native.checked_sub(amount_to_unstake).map_err(StdError::overflow)?;
-    supply.unstakings += amount_to_unstake;
+    let liquid_supply = get_liquid_shares(&deps.querier, &config, &supply)?;
+    let amount_to_unstake = liquid_to_native(amount, supply.native, liquid_supply);
+    if amount_to_unstake.is_zero() || amount_to_unstake < config.min_unstake {
+        return Err(ContractError::UnstakeBelowMinimum { min: config.min_unstake });
+    }
+    let queued = match config.queue_mode {
+        QueueMode::Native => {
+            let units = native_to_units(&supply, amount_to_unstake);
+            supply.native = supply.native.checked_sub(amount_to_unstake).map_err(StdError::overflow)?;
+            supply.unstakings += amount_to_unstake;
+            supply.unstaking_units += units;
+            units
+        }
+        // the burned liquid tokens keep their share of the native tokens until paid out
+        QueueMode::Liquid => {
+            supply.unstaking_liquid += amount;
+            amount
+        }
+    };
+    if config.liquid_denom.is_some() {
+        supply.issued = supply.issued.checked_sub(amount).map_err(StdError::overflow)?;
+    }
     TOTAL_SUPPLY.save(deps.storage, &supply)?;
-    linked_list_append(deps.storage, sender.clone(), amount_to_unstake, env.block.height)?;
+    let mut epoch = CURRENT_EPOCH.load(deps.storage)?;
+    epoch.pending += queued;
+    CURRENT_EPOCH.save(deps.storage, &epoch)?;
+    linked_list_append(deps.storage, recipient.clone(), queued, env.block.height, epoch.id)?;
     UNDER_UNSTAKING.update(
         deps.storage,
-        &sender,
-        |claimable: Option<Uint128>| -> StdResult<_> { Ok(claimable.unwrap_or_default() + amount_to_unstake) },
+        &recipient,
+        |claimable: Option<Uint128>| -> StdResult<_> { Ok(claimable.unwrap_or_default() + queued) },
     )?;
-    let msg2 = to_binary(&ExecuteMsg::_PerformCheck {})?;
-    
-    let res = Response::new()
-        .add_message(msg1)
-        .add_message(WasmMsg::Execute {
-            contract_addr: contract_addr.to_string(),
-            msg: msg2,
-            funds: vec![],
-        })
+    // the cw20 burn runs after this message, a process finishing right away must not count those tokens
+    let burning = match config.liquid_denom {
+        Some(_) => Uint128::zero(),
+        None => amount,
+    };
+    let res = Response::new().add_message(msg1);
+    let res = merge_response(res, perform_check(deps.branch(), &env, None, None, burning)?)
         .add_attribute("action", "unstake")
         .add_attribute("from", sender)
+        .add_attribute("recipient", recipient)
         .add_attribute("amount", amount);
     Ok(res)
 }
 
+// unstake the token-factory liquid tokens sent with the message
+pub fn execute_unstake_denom(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let liquid_denom = config.liquid_denom.ok_or(ContractError::NoLiquidDenom {})?;
+    if config.paused.unstake {
+        return Err(ContractError::Paused { operation: "unstake".to_string() });
+    }
+    // only the liquid denom, other coins would be stuck in the contract
+    let amount = must_pay(&info, &liquid_denom)?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
+    execute_unstake(deps, env, info.sender.clone(), recipient, amount)
+}
+
+pub fn execute_cancel_unstake(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    request_id: u64,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // cancelling changes the unstaking queue and stakes again, it is blocked by either pause
+    if config.paused.unstake {
+        return Err(ContractError::Paused { operation: "unstake".to_string() });
+    }
+    if config.paused.stake {
+        return Err(ContractError::Paused { operation: "stake".to_string() });
+    }
+    let request = NODES
+        .may_load(deps.storage, request_id)?
+        .ok_or(ContractError::UnstakeRequestNotFound { id: request_id })?;
+    // only allow the receiver of the request to call
+    if info.sender != request.receiver {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the part already paid out stays claimable, the rest leaves the queue
+    let unpaid = request.value;
+    linked_list_remove(deps.storage, request_id)?;
+    let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let undelegation = UNDELEGATIONS.may_load(deps.storage, request.epoch)?;
+    let native_amount = if config.queue_mode == QueueMode::Liquid && undelegation.is_none() {
+        // liquid requests of the open epoch still follow the exchange rate
+        let shares = get_liquid_shares(&deps.querier, &config, &supply)?;
+        let native_amount = liquid_to_native(unpaid, supply.native, shares);
+        supply.native = supply.native.checked_sub(native_amount).map_err(StdError::overflow)?;
+        supply.unstaking_liquid = supply.unstaking_liquid.checked_sub(unpaid).map_err(StdError::overflow)?;
+        native_amount
+    } else {
+        let units = match &undelegation {
+            Some(undelegation) => epoch_units(&config, undelegation, unpaid),
+            None => unpaid,
+        };
+        let native_amount = units_to_native(&supply, units);
+        supply.unstakings = supply.unstakings.checked_sub(native_amount).map_err(StdError::overflow)?;
+        supply.unstaking_units = supply.unstaking_units.checked_sub(units).map_err(StdError::overflow)?;
+        native_amount
+    };
+    TOTAL_SUPPLY.save(deps.storage, &supply)?;
+    UNDER_UNSTAKING.update(
+        deps.storage,
+        &info.sender,
+        |unstaking: Option<Uint128>| -> StdResult<_> { Ok(unstaking.unwrap_or_default().checked_sub(unpaid)?) },
+    )?;
+    // not undelegated yet, keep it bonded
+    let mut epoch = CURRENT_EPOCH.load(deps.storage)?;
+    if request.epoch == epoch.id {
+        epoch.pending = epoch.pending.checked_sub(unpaid).map_err(StdError::overflow)?;
+        CURRENT_EPOCH.save(deps.storage, &epoch)?;
+    }
+
+    // liquid tokens are minted back once staking rewards are compounded, like for a new stake
+    let mint = if native_amount.is_zero() {
+        None
+    } else {
+        Some(PendingMint {
+            receiver: info.sender.clone(),
+            native_amount,
+        })
+    };
+    let res = perform_check(deps, &env, mint, None, Uint128::zero())?
+        .add_attribute("action", "cancelUnstake")
+        .add_attribute("from", info.sender)
+        .add_attribute("request_id", request_id.to_string())
+        .add_attribute("amount", native_amount);
+    Ok(res)
+}
+
 pub fn execute_receive(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     env: Env,
     info: MessageInfo,
     wrapper: Cw20ReceiveMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     // info.sender is the address of the cw20 contract (that re-sent this message).
     // wrapper.sender is the address of the user that requested the cw20 contract to send this.
     // This cannot be fully trusted (the cw20 contract can fake it), so only use it for actions
@@ -347,16 +964,118 @@ pub fn execute_receive(
     if info.sender != config.liquid_token_addr {
         return Err(ContractError::Unauthorized {});
     }
+    // an empty payload used to mean unstake, make callers say what they want
+    if wrapper.msg.is_empty() {
+        return Err(ContractError::EmptyReceiveMsg {});
+    }
+    let msg: ReceiveMsg = from_binary(&wrapper.msg)
+        .map_err(|err| ContractError::InvalidReceiveMsg { reason: err.to_string() })?;
+
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let unstake = matches!(msg, ReceiveMsg::Unstake { .. } | ReceiveMsg::InstantUnstake { .. });
+    if unstake && config.paused.unstake {
+        return Err(ContractError::Paused { operation: "unstake".to_string() });
+    }
+    match msg {
+        ReceiveMsg::Unstake { recipient } => {
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?,
+                None => sender.clone(),
+            };
+            execute_unstake(deps, env, sender, recipient, wrapper.amount)
+        }
+        ReceiveMsg::InstantUnstake { min_native, recipient } => {
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?,
+                None => sender.clone(),
+            };
+            execute_instant_unstake(deps, env, sender, recipient, wrapper.amount, min_native)
+        }
+        ReceiveMsg::LockVotingPower {} => lock_voting_power(deps, env, sender, wrapper.amount),
+    }
+}
+
+// swap the liquid tokens for native tokens through the swap contract,
+// the native tokens received are forwarded to the recipient in reply
+pub fn execute_instant_unstake(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    sender: Addr,
+    recipient: Addr,
+    amount: Uint128,
+    min_native: Uint128,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let swap_contract = config.swap_contract.clone().ok_or(ContractError::NoSwapContract {})?;
+    let balance_before = deps
+        .querier
+        .query_balance(&env.contract.address, &config.bond_denom)?.amount;
+    let pending = PendingInstantUnstake {
+        recipient: recipient.clone(),
+        balance_before,
+        min_native,
+    };
+    PENDING_INSTANT_UNSTAKE.save(deps.storage, &pending)?;
+
+    // the swap contract pays the native tokens to this contract
+    let msg = WasmMsg::Execute {
+        contract_addr: config.liquid_token_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: swap_contract.to_string(),
+            amount,
+            msg: Binary::default(),
+        })?,
+        funds: vec![],
+    };
+    let res = Response::new()
+        .add_submessage(SubMsg::reply_on_success(msg, INSTANT_UNSTAKE_REPLY_ID))
+        .add_attribute("action", "instantUnstake")
+        .add_attribute("from", sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+// forward the native tokens paid by the swap contract to the recipient
+pub fn reply_instant_unstake(deps: DepsMut<BindingQuery>, env: Env) -> Result<Response<BindingMsg>, ContractError> {
+    let pending = PENDING_INSTANT_UNSTAKE.load(deps.storage)?;
+    PENDING_INSTANT_UNSTAKE.remove(deps.storage);
+    let config = CONFIG.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &config.bond_denom)?.amount;
+    let received = balance.checked_sub(pending.balance_before).map_err(StdError::overflow)?;
+    if received < pending.min_native {
+        return Err(ContractError::InstantUnstakeBelowMinimum { amount: received, min: pending.min_native });
+    }
 
-    let api = deps.api;
-    execute_unstake(deps, env, api.addr_validate(&wrapper.sender)?, wrapper.amount)
+    let mut res = Response::new().add_event(
+        Event::new("instant_unstake")
+            .add_attribute("recipient", pending.recipient.clone())
+            .add_attribute("received", received),
+    );
+    if !received.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: pending.recipient.to_string(),
+            amount: coins(received.u128(), &config.bond_denom),
+        });
+    }
+    Ok(res)
 }
 
 pub fn execute_claim(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     info: MessageInfo,
-) -> Result<Response, ContractError> {
+    recipient: Option<String>,
+) -> Result<Response<BindingMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    if config.paused.claim {
+        return Err(ContractError::Paused { operation: "claim".to_string() });
+    }
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
 
     let mut to_send:Uint128 = Uint128::zero();
     CLAIMABLE.update(
@@ -376,56 +1095,904 @@ pub fn execute_claim(
         Ok(supply)
     })?;
     
-    // transfer tokens to the sender
+    // transfer tokens to the recipient
     let res = Response::new()
         .add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
+            to_address: recipient.to_string(),
             amount: coins(to_send.u128(), config.bond_denom),
         })
         .add_attribute("action", "claim")
         .add_attribute("from", info.sender)
+        .add_attribute("recipient", recipient)
         .add_attribute("amount", to_send);
     Ok(res)
 }
 
-pub fn execute_set_liquid_token(
-    deps: DepsMut,
+// withdraw the Archway rewards of this contract, they are added to the native tokens of liquid token holders
+// and bonded the next time the available native tokens are processed
+pub fn execute_withdraw_archway_rewards(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
     info: MessageInfo,
-    address: Addr,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    // only allow owner to call 
-    if info.sender != config.owner {
-        return Err(ContractError::Unauthorized {});
+    let records = query_rewards_records(&deps.querier, env.contract.address.to_string(), ARCHWAY_RECORDS_LIMIT)?;
+    if records.is_empty() {
+        return Err(ContractError::NoArchwayRewards {});
+    }
+    let rewards: Uint128 = records
+        .iter()
+        .flat_map(|record| record.rewards.iter())
+        .filter(|reward| reward.denom == config.bond_denom)
+        .map(|reward| reward.amount)
+        .sum();
+    let mut supply = TOTAL_SUPPLY.load(deps.storage)?;
+    match config.reward_mode {
+        // rewards don't change the ratio in index reward mode
+        RewardMode::Index => {
+            let locked = TOTAL_VOTING_POWER.may_load(deps.storage)?.unwrap_or_default();
+            add_rewards(deps.storage, &mut supply, rewards, locked)?;
+            TOTAL_SUPPLY.save(deps.storage, &supply)?;
+        }
+        RewardMode::Compound => {
+            supply.native += rewards;
+            TOTAL_SUPPLY.save(deps.storage, &supply)?;
+            let issued = get_liquid_shares(&deps.querier, &config, &supply)?;
+            save_ratio_snapshot(deps.storage, &env, &supply, issued)?;
+        }
     }
-    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
-        config.liquid_token_addr = address.clone();
-        Ok(config)
-    })?;
 
     let res = Response::new()
-        .add_attribute("action", "setLiquidToken")
+        .add_message(BindingMsg::WithdrawRewards {
+            records_limit: None,
+            record_ids: records.iter().map(|record| record.id).collect(),
+        })
+        .add_attribute("action", "withdrawArchwayRewards")
         .add_attribute("from", info.sender)
-        .add_attribute("address", address);
+        .add_attribute("records", records.len().to_string())
+        .add_attribute("rewards", rewards);
     Ok(res)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::ClaimableOf { address } => {
-            to_binary(&query_claimable_of(deps, address)?)
-        },
-        QueryMsg::ConfigInfo {} => to_binary(&query_config(deps)?),
-        QueryMsg::StatusInfo {} => to_binary(&query_status(deps, _env)?),
-        QueryMsg::UnstakingQueue {} => to_binary(&query_unstaking_queue(deps)?),
+// compound the staking rewards, the caller is paid the harvest bounty
+pub fn execute_harvest(deps: DepsMut<BindingQuery>, env: Env, info: MessageInfo) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if let Some(last_harvest) = LAST_HARVEST.may_load(deps.storage)? {
+        let height = last_harvest + config.harvest_interval;
+        if env.block.height < height {
+            return Err(ContractError::HarvestTooSoon { height });
+        }
+    }
+    LAST_HARVEST.save(deps.storage, &env.block.height)?;
+
+    let res = perform_check(deps, &env, None, Some(info.sender.clone()), Uint128::zero())?
+        .add_attribute("action", "harvest")
+        .add_attribute("from", info.sender);
+    Ok(res)
+}
+
+// load_open_gov_proposal returns a registered governance proposal still open for voting
+fn load_open_gov_proposal(storage: &dyn Storage, env: &Env, proposal_id: u64) -> Result<GovProposal, ContractError> {
+    let proposal = GOV_PROPOSALS
+        .may_load(storage, proposal_id)?
+        .ok_or(ContractError::GovProposalNotFound { proposal_id })?;
+    if env.block.time >= proposal.voting_end {
+        return Err(ContractError::GovVotingClosed { proposal_id });
+    }
+    Ok(proposal)
+}
+
+// open_gov_proposals returns the ids of the registered governance proposals still open for voting
+fn open_gov_proposals(storage: &dyn Storage, now: Timestamp) -> StdResult<Vec<u64>> {
+    let mut open = vec![];
+    for proposal_id in OPEN_GOV_PROPOSALS.may_load(storage)?.unwrap_or_default() {
+        if GOV_PROPOSALS.load(storage, proposal_id)?.voting_end > now {
+            open.push(proposal_id);
+        }
+    }
+    Ok(open)
+}
+
+// record the vote of a liquid token holder on a registered governance proposal
+pub fn execute_vote(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    option: VoteOption,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut proposal = load_open_gov_proposal(deps.storage, &env, proposal_id)?;
+    // tokens locked or moved after the registration don't count, they can't vote twice
+    let weight = VOTING_POWER
+        .may_load_at_height(deps.storage, &info.sender, proposal.start_height)?
+        .unwrap_or_default();
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower {});
+    }
+    // a new vote replaces the previous one
+    if let Some(previous) = VOTES.may_load(deps.storage, (proposal_id, &info.sender))? {
+        proposal.tally.remove(&previous.option, previous.weight);
+    }
+    proposal.tally.add(&option, weight);
+    GOV_PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    let vote = GovVote { option: option.clone(), weight };
+    VOTES.save(deps.storage, (proposal_id, &info.sender), &vote)?;
+
+    let res = Response::new()
+        .add_attribute("action", "vote")
+        .add_attribute("from", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("option", format!("{:?}", option))
+        .add_attribute("weight", weight);
+    Ok(res)
+}
+
+// cast the vote of the contract on a governance proposal, split according to the tally of the holders
+pub fn execute_cast_vote(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let tally = load_open_gov_proposal(deps.storage, &env, proposal_id)?.tally;
+    if tally.total().is_zero() {
+        return Err(ContractError::NoVotes { proposal_id });
+    }
+
+    let res = Response::new()
+        .add_message(GovMsg::VoteWeighted {
+            proposal_id,
+            options: weighted_options(&tally),
+        })
+        .add_attribute("action", "castVote")
+        .add_attribute("from", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("yes", tally.yes)
+        .add_attribute("no", tally.no)
+        .add_attribute("abstain", tally.abstain)
+        .add_attribute("no_with_veto", tally.no_with_veto);
+    Ok(res)
+}
+
+// send the rewards accrued by the caller in index reward mode
+pub fn execute_claim_rewards(deps: DepsMut<BindingQuery>, info: MessageInfo) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.reward_mode != RewardMode::Index {
+        return Err(ContractError::NoRewardIndex {});
+    }
+    if config.paused.claim {
+        return Err(ContractError::Paused { operation: "claim".to_string() });
+    }
+    let mut rewards = checkpoint(deps.storage, &info.sender)?;
+    let amount = rewards.pending;
+    if amount.is_zero() {
+        return Err(ContractError::NoRewards {});
+    }
+    rewards.pending = Uint128::zero();
+    HOLDER_REWARDS.save(deps.storage, &info.sender, &rewards)?;
+    TOTAL_SUPPLY.update(deps.storage, |mut supply| -> StdResult<_> {
+        supply.rewards = supply.rewards.checked_sub(amount)?;
+        Ok(supply)
+    })?;
+
+    let res = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), config.bond_denom),
+        })
+        .add_attribute("action", "claimRewards")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+// checkpoint the rewards accrued by the liquid tokens the caller locked
+pub fn execute_update_index(deps: DepsMut<BindingQuery>, info: MessageInfo) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.reward_mode != RewardMode::Index {
+        return Err(ContractError::NoRewardIndex {});
+    }
+    let rewards = checkpoint(deps.storage, &info.sender)?;
+
+    let res = Response::new()
+        .add_attribute("action", "updateIndex")
+        .add_attribute("from", info.sender)
+        .add_attribute("pending", rewards.pending);
+    Ok(res)
+}
+
+pub fn execute_lock_voting_power_denom(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let liquid_denom = config.liquid_denom.ok_or(ContractError::NoLiquidDenom {})?;
+    let amount = must_pay(&info, &liquid_denom)?;
+    lock_voting_power(deps, env, info.sender.clone(), amount)
+}
+
+// lock liquid tokens of `holder` as voting power on validator proposals, they earn rewards in index reward mode
+pub fn lock_voting_power(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    holder: Addr,
+    amount: Uint128,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.reward_mode == RewardMode::Index {
+        checkpoint(deps.storage, &holder)?;
+    }
+    let height = env.block.height;
+    let power = VOTING_POWER.update(deps.storage, &holder, height, |power| -> StdResult<_> {
+        Ok(power.unwrap_or_default() + amount)
+    })?;
+    TOTAL_VOTING_POWER.update(deps.storage, height, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default() + amount)
+    })?;
+
+    let res = Response::new()
+        .add_attribute("action", "lockVotingPower")
+        .add_attribute("from", holder)
+        .add_attribute("amount", amount)
+        .add_attribute("power", power);
+    Ok(res)
+}
+
+pub fn execute_unlock_voting_power(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.reward_mode == RewardMode::Index {
+        checkpoint(deps.storage, &info.sender)?;
+    }
+    let height = env.block.height;
+    // votes already cast keep the voting power of the proposal start height
+    let power = VOTING_POWER.update(deps.storage, &info.sender, height, |power| -> Result<_, ContractError> {
+        let available = power.unwrap_or_default();
+        available
+            .checked_sub(amount)
+            .map_err(|_| ContractError::InsufficientVotingPower { available })
+    })?;
+    TOTAL_VOTING_POWER.update(deps.storage, height, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_sub(amount)?)
+    })?;
+
+    let res = Response::new()
+        .add_message(transfer_msg(&config, &info.sender, amount)?)
+        .add_attribute("action", "unlockVotingPower")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("power", power);
+    Ok(res)
+}
+
+pub fn execute_propose_validator_changes(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    changes: Vec<ValidatorChange>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let rules = config.validator_gov.clone().ok_or(ContractError::ValidatorGovDisabled {})?;
+    if changes.is_empty() {
+        return Err(ContractError::EmptyValidatorProposal {});
+    }
+    // reject changes that can't apply to the current set, they are checked again on execution
+    let validators = apply_validator_changes(&config.validators, &changes)?;
+    validate_validators(&deps.querier, &validators)?;
+
+    // voting power is taken at the start of this block, tokens locked from now on don't count
+    let start_height = env.block.height;
+    let power = VOTING_POWER
+        .may_load_at_height(deps.storage, &info.sender, start_height)?
+        .unwrap_or_default();
+    if power.is_zero() {
+        return Err(ContractError::NoVotingPower {});
+    }
+    // the quorum is measured against every liquid token issued, the locked ones are held by the contract
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let total_power = get_token_supply(&deps.querier, &config, &supply)?;
+
+    let id = VALIDATOR_PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    VALIDATOR_PROPOSAL_COUNT.save(deps.storage, &id)?;
+    let proposal = ValidatorProposal {
+        proposer: info.sender.clone(),
+        changes,
+        start_height,
+        voting_end: env.block.time.plus_seconds(rules.voting_period),
+        total_power,
+        rules,
+        yes: Uint128::zero(),
+        no: Uint128::zero(),
+        abstain: Uint128::zero(),
+        executed: false,
+    };
+    VALIDATOR_PROPOSALS.save(deps.storage, id, &proposal)?;
+
+    let res = Response::new()
+        .add_attribute("action", "proposeValidatorChanges")
+        .add_attribute("from", info.sender)
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("voting_end", proposal.voting_end.to_string());
+    Ok(res)
+}
+
+pub fn execute_vote_validator_proposal(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    ballot: Ballot,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut proposal = VALIDATOR_PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ValidatorProposalNotFound { id: proposal_id })?;
+    if env.block.time >= proposal.voting_end {
+        return Err(ContractError::VotingClosed { id: proposal_id });
+    }
+    let weight = VOTING_POWER
+        .may_load_at_height(deps.storage, &info.sender, proposal.start_height)?
+        .unwrap_or_default();
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower {});
+    }
+
+    // a new vote replaces the previous one
+    if let Some(previous) = VALIDATOR_VOTES.may_load(deps.storage, (proposal_id, &info.sender))? {
+        let votes = match previous.ballot {
+            Ballot::Yes => &mut proposal.yes,
+            Ballot::No => &mut proposal.no,
+            Ballot::Abstain => &mut proposal.abstain,
+        };
+        *votes = votes.checked_sub(previous.weight).map_err(StdError::overflow)?;
+    }
+    match ballot {
+        Ballot::Yes => proposal.yes += weight,
+        Ballot::No => proposal.no += weight,
+        Ballot::Abstain => proposal.abstain += weight,
+    }
+    VALIDATOR_PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    VALIDATOR_VOTES.save(deps.storage, (proposal_id, &info.sender), &ValidatorVote { ballot: ballot.clone(), weight })?;
+
+    let res = Response::new()
+        .add_attribute("action", "voteValidatorProposal")
+        .add_attribute("from", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("ballot", format!("{:?}", ballot))
+        .add_attribute("weight", weight);
+    Ok(res)
+}
+
+// apply the changes of a passed validator proposal to the validator set, anyone can call it
+pub fn execute_validator_proposal(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // the owner can stop passed proposals from being executed by disabling validator proposals
+    if config.validator_gov.is_none() {
+        return Err(ContractError::ValidatorGovDisabled {});
+    }
+    let mut proposal = VALIDATOR_PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ValidatorProposalNotFound { id: proposal_id })?;
+    match proposal_status(&proposal, env.block.time) {
+        ProposalStatus::Passed => {}
+        ProposalStatus::Executed => return Err(ContractError::ValidatorProposalExecuted { id: proposal_id }),
+        _ => return Err(ContractError::ValidatorProposalNotPassed { id: proposal_id }),
+    }
+    let time = proposal.voting_end.plus_seconds(proposal.rules.timelock);
+    if env.block.time < time {
+        return Err(ContractError::TimelockNotExpired { time });
+    }
+
+    let validators = apply_validator_changes(&config.validators, &proposal.changes)?;
+    validate_validators(&deps.querier, &validators)?;
+    // like UpdateValidators, existing delegations follow the new weights over time
+    config.validators = validators;
+    CONFIG.save(deps.storage, &config)?;
+    proposal.executed = true;
+    VALIDATOR_PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+
+    let res = Response::new()
+        .add_attribute("action", "executeValidatorProposal")
+        .add_attribute("from", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("validators", config.validators.len().to_string());
+    Ok(res)
+}
+
+pub fn execute_update_validators(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    validators: Vec<ValidatorInfo>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    validate_validators(&deps.querier, &validators)?;
+    // existing delegations are not moved here, they follow the new weights
+    // as tokens get delegated and undelegated
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        config.validators = validators.clone();
+        Ok(config)
+    })?;
+
+    let res = Response::new()
+        .add_attribute("action", "updateValidators")
+        .add_attribute("from", info.sender)
+        .add_attribute("validators", validators.len().to_string());
+    Ok(res)
+}
+
+pub fn execute_redelegate(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    src: String,
+    dst: String,
+    amount: Uint128,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::ZeroRedelegation {});
+    }
+    if !config.validators.iter().any(|v| v.address == dst) {
+        return Err(ContractError::ValidatorNotInSet { validator: dst });
+    }
+    // respect the chain's redelegation limits instead of letting the message fail
+    let available = redelegatable(&deps.querier, &env.contract.address, &src)?;
+    if amount > available {
+        return Err(ContractError::RedelegationUnavailable { validator: src, available });
+    }
+    if redelegation_entries(deps.storage, &src, &dst, env.block.time)?.len() >= MAX_REDELEGATION_ENTRIES {
+        return Err(ContractError::TooManyRedelegations { src, dst });
+    }
+    let completion = env.block.time.plus_seconds(config.unbonding_period);
+    record_redelegation(deps.storage, &src, &dst, env.block.time, completion)?;
+
+    // the chain withdraws the rewards of both validators, they are processed in reply like harvested ones
+    let balance_before = deps
+        .querier
+        .query_balance(&env.contract.address, &config.bond_denom)?.amount;
+    let pending = PendingProcess { balance_before, mint: None, harvester: None, burning: Uint128::zero() };
+    PENDING_PROCESS.save(deps.storage, &pending)?;
+    let redelegate = StakingMsg::Redelegate {
+        src_validator: src.clone(),
+        dst_validator: dst.clone(),
+        amount: coin(amount.u128(), &config.bond_denom),
+    };
+
+    let res = Response::new()
+        .add_submessage(SubMsg::reply_on_success(redelegate, PROCESS_TOKEN_REPLY_ID))
+        .add_attribute("action", "redelegate")
+        .add_attribute("from", info.sender)
+        .add_attribute("src", src)
+        .add_attribute("dst", dst)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn execute_set_rebalance_threshold(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    threshold: Decimal,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    config.rebalance_threshold = threshold;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "setRebalanceThreshold")
+        .add_attribute("from", info.sender)
+        .add_attribute("threshold", threshold.to_string());
+    Ok(res)
+}
+
+pub fn execute_set_protocol_fee(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    protocol_fee: u64,
+    treasury: String,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if protocol_fee > FEE_DENOMINATOR {
+        return Err(ContractError::InvalidProtocolFee { max: FEE_DENOMINATOR });
+    }
+    if protocol_fee + config.harvest_bounty > FEE_DENOMINATOR {
+        return Err(ContractError::InvalidHarvestBounty { max: FEE_DENOMINATOR });
+    }
+    config.protocol_fee = protocol_fee;
+    config.treasury = deps.api.addr_validate(&treasury)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "setProtocolFee")
+        .add_attribute("from", info.sender)
+        .add_attribute("protocol_fee", protocol_fee.to_string())
+        .add_attribute("treasury", treasury);
+    Ok(res)
+}
+
+pub fn execute_set_harvest_bounty(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    harvest_bounty: u64,
+    harvest_interval: u64,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if config.protocol_fee + harvest_bounty > FEE_DENOMINATOR {
+        return Err(ContractError::InvalidHarvestBounty { max: FEE_DENOMINATOR });
+    }
+    config.harvest_bounty = harvest_bounty;
+    config.harvest_interval = harvest_interval;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "setHarvestBounty")
+        .add_attribute("from", info.sender)
+        .add_attribute("harvest_bounty", harvest_bounty.to_string())
+        .add_attribute("harvest_interval", harvest_interval.to_string());
+    Ok(res)
+}
+
+pub fn execute_set_stake_limits(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    max_tvl: Option<Uint128>,
+    min_stake: Uint128,
+    min_unstake: Uint128,
+    max_stake_per_address: Option<Uint128>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    config.max_tvl = max_tvl;
+    config.min_stake = min_stake;
+    config.min_unstake = min_unstake;
+    config.max_stake_per_address = max_stake_per_address;
+    CONFIG.save(deps.storage, &config)?;
+
+    let none = || "none".to_string();
+    let res = Response::new()
+        .add_attribute("action", "setStakeLimits")
+        .add_attribute("from", info.sender)
+        .add_attribute("max_tvl", max_tvl.map_or_else(none, |cap| cap.to_string()))
+        .add_attribute("min_stake", min_stake)
+        .add_attribute("min_unstake", min_unstake)
+        .add_attribute("max_stake_per_address", max_stake_per_address.map_or_else(none, |limit| limit.to_string()));
+    Ok(res)
+}
+
+pub fn execute_set_swap_contract(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    swap_contract: Option<String>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    config.swap_contract = swap_contract.map(|swap_contract| deps.api.addr_validate(&swap_contract)).transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "setSwapContract")
+        .add_attribute("from", info.sender)
+        .add_attribute("swap_contract", config.swap_contract.map(String::from).unwrap_or_default());
+    Ok(res)
+}
+
+pub fn execute_set_validator_gov(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    validator_gov: Option<ValidatorGovConfig>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(validator_gov) = &validator_gov {
+        validate_validator_gov(validator_gov)?;
+    }
+    config.validator_gov = validator_gov;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "setValidatorGov")
+        .add_attribute("from", info.sender)
+        .add_attribute("enabled", config.validator_gov.is_some().to_string());
+    Ok(res)
+}
+
+pub fn execute_register_proposal(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    voting_end: Timestamp,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.time >= voting_end {
+        return Err(ContractError::GovVotingClosed { proposal_id });
+    }
+    if GOV_PROPOSALS.has(deps.storage, proposal_id) {
+        return Err(ContractError::GovProposalRegistered { proposal_id });
+    }
+    // closed proposals are dropped from the open ones
+    let mut open = open_gov_proposals(deps.storage, env.block.time)?;
+    if open.len() as u64 >= MAX_OPEN_GOV_PROPOSALS {
+        return Err(ContractError::TooManyOpenProposals { max: MAX_OPEN_GOV_PROPOSALS });
+    }
+    let proposal = GovProposal {
+        voting_end,
+        start_height: env.block.height,
+        tally: Tally::default(),
+    };
+    GOV_PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    open.push(proposal_id);
+    OPEN_GOV_PROPOSALS.save(deps.storage, &open)?;
+
+    let res = Response::new()
+        .add_attribute("action", "registerProposal")
+        .add_attribute("from", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voting_end", voting_end.to_string());
+    Ok(res)
+}
+
+pub fn execute_set_guardian(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    config.guardian = guardian.map(|guardian| deps.api.addr_validate(&guardian)).transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "setGuardian")
+        .add_attribute("from", info.sender)
+        .add_attribute("guardian", config.guardian.map(String::from).unwrap_or_default());
+    Ok(res)
+}
+
+pub fn execute_pause(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    stake: bool,
+    unstake: bool,
+    claim: bool,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow guardian or owner to call 
+    if info.sender != config.owner && Some(&info.sender) != config.guardian.as_ref() {
+        return Err(ContractError::Unauthorized {});
+    }
+    // pausing never unpauses an operation that is already paused
+    config.paused.stake |= stake;
+    config.paused.unstake |= unstake;
+    config.paused.claim |= claim;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "pause")
+        .add_attribute("from", info.sender)
+        .add_attribute("stake", config.paused.stake.to_string())
+        .add_attribute("unstake", config.paused.unstake.to_string())
+        .add_attribute("claim", config.paused.claim.to_string());
+    Ok(res)
+}
+
+pub fn execute_unpause(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+    stake: bool,
+    unstake: bool,
+    claim: bool,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    config.paused.stake &= !stake;
+    config.paused.unstake &= !unstake;
+    config.paused.claim &= !claim;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "unpause")
+        .add_attribute("from", info.sender)
+        .add_attribute("stake", config.paused.stake.to_string())
+        .add_attribute("unstake", config.paused.unstake.to_string())
+        .add_attribute("claim", config.paused.claim.to_string());
+    Ok(res)
+}
+
+pub fn execute_propose_new_owner(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: Option<u64>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let proposal = OwnershipProposal {
+        owner: deps.api.addr_validate(&owner)?,
+        expires: expires_in.map(|seconds| env.block.time.plus_seconds(seconds)),
+    };
+    // a new proposal replaces the pending one
+    OWNERSHIP_PROPOSAL.save(deps.storage, &proposal)?;
+
+    let res = Response::new()
+        .add_attribute("action", "proposeNewOwner")
+        .add_attribute("from", info.sender)
+        .add_attribute("owner", proposal.owner);
+    Ok(res)
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let proposal = OWNERSHIP_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoOwnershipProposal {})?;
+    // only allow proposed owner to call 
+    if info.sender != proposal.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if proposal.expires.is_some_and(|expires| env.block.time >= expires) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+    let previous_owner = CONFIG.load(deps.storage)?.owner;
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        config.owner = proposal.owner;
+        Ok(config)
+    })?;
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    let res = Response::new()
+        .add_attribute("action", "acceptOwnership")
+        .add_attribute("from", info.sender)
+        .add_attribute("previous_owner", previous_owner);
+    Ok(res)
+}
+
+pub fn execute_cancel_ownership_proposal(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if OWNERSHIP_PROPOSAL.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoOwnershipProposal {});
+    }
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    let res = Response::new()
+        .add_attribute("action", "cancelOwnershipProposal")
+        .add_attribute("from", info.sender);
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut<BindingQuery>, env: Env, _msg: MigrateMsg) -> Result<Response<BindingMsg>, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract { contract: stored.contract });
+    }
+    let stored_version: Version = stored.version.parse()?;
+    let version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > version {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            version: CONTRACT_VERSION.to_string(),
+        });
+    }
+    // run the state transforms of every version between the stored one and this one
+    let mut res = Response::new();
+    if stored_version < Version::new(0, 2, 0) {
+        res = res.add_messages(migrate_from_v0_1(deps.branch(), &env)?);
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    res = res
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<BindingQuery>, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::ClaimableOf { address } => {
+            to_binary(&query_claimable_of(deps, address)?)
+        },
+        QueryMsg::ConfigInfo {} => to_binary(&query_config(deps)?),
+        QueryMsg::StatusInfo {} => to_binary(&query_status(deps, _env)?),
+        QueryMsg::UnstakingQueue { start_after, limit } => {
+            to_binary(&query_unstaking_queue(deps, start_after, limit)?)
+        },
+        QueryMsg::UnstakingRequestsOf { address, start_after, limit } => {
+            to_binary(&query_unstaking_requests_of(deps, address, start_after, limit)?)
+        },
         QueryMsg::UnderUnstakingOf { address } => {
             to_binary(&query_under_unstaking_of(deps, address)?)
         },
+        QueryMsg::PendingUnbondingsOf { address } => {
+            to_binary(&query_pending_unbondings_of(deps, _env, address)?)
+        },
+        QueryMsg::RatioHistory { start_after, limit } => {
+            to_binary(&query_ratio_history(deps, start_after, limit)?)
+        },
+        QueryMsg::SimulateStake { amount } => to_binary(&query_simulate_stake(deps, _env, amount)?),
+        QueryMsg::SimulateUnstake { liquid_amount } => {
+            to_binary(&query_simulate_unstake(deps, _env, liquid_amount)?)
+        },
+        QueryMsg::ProposalTally { proposal_id } => to_binary(&query_proposal_tally(deps, proposal_id)?),
+        QueryMsg::VoteOf { proposal_id, address } => to_binary(&query_vote_of(deps, proposal_id, address)?),
+        QueryMsg::RewardsOf { address } => to_binary(&query_rewards_of(deps, address)?),
+        QueryMsg::VotingPowerOf { address } => to_binary(&query_voting_power_of(deps, address)?),
+        QueryMsg::ValidatorProposal { proposal_id } => {
+            to_binary(&query_validator_proposal(deps, _env, proposal_id)?)
+        }
+        QueryMsg::ValidatorProposals { start_after, limit } => {
+            to_binary(&query_validator_proposals(deps, _env, start_after, limit)?)
+        }
+        QueryMsg::EstimatedApr { window_seconds } => {
+            to_binary(&query_estimated_apr(deps, window_seconds)?)
+        },
     }
 }
 
-pub fn query_claimable_of(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+pub fn query_claimable_of(deps: Deps<BindingQuery>, address: String) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
     let claimable = CLAIMABLE
         .may_load(deps.storage, &address)?
@@ -433,19 +2000,40 @@ pub fn query_claimable_of(deps: Deps, address: String) -> StdResult<BalanceRespo
     Ok(BalanceResponse { balance: claimable })
 }
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+pub fn query_config(deps: Deps<BindingQuery>) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
+    let proposal = OWNERSHIP_PROPOSAL.may_load(deps.storage)?;
 
     let res = ConfigResponse {
         owner: config.owner.to_string(),
         bond_denom: config.bond_denom,
         liquid_token_addr: config.liquid_token_addr.to_string(),
-        validator: config.validator,
+        liquid_denom: config.liquid_denom,
+        validators: config.validators,
+        unbonding_period: config.unbonding_period,
+        rebalance_threshold: config.rebalance_threshold,
+        epoch_period: config.epoch_period,
+        protocol_fee: config.protocol_fee,
+        treasury: config.treasury.to_string(),
+        harvest_bounty: config.harvest_bounty,
+        harvest_interval: config.harvest_interval,
+        max_tvl: config.max_tvl,
+        min_stake: config.min_stake,
+        min_unstake: config.min_unstake,
+        max_stake_per_address: config.max_stake_per_address,
+        swap_contract: config.swap_contract.map(String::from),
+        guardian: config.guardian.map(String::from),
+        paused: config.paused,
+        validator_gov: config.validator_gov,
+        queue_mode: config.queue_mode,
+        reward_mode: config.reward_mode,
+        pending_owner: proposal.as_ref().map(|p| p.owner.to_string()),
+        pending_owner_expires: proposal.and_then(|p| p.expires),
     };
     Ok(res)
 }
 
-pub fn query_status(deps: Deps, _env: Env) -> StdResult<StatusResponse> {
+pub fn query_status(deps: Deps<BindingQuery>, _env: Env) -> StdResult<StatusResponse> {
     let config = CONFIG.load(deps.storage)?;
     let supply = TOTAL_SUPPLY.load(deps.storage)?;
 
@@ -453,12 +2041,16 @@ pub fn query_status(deps: Deps, _env: Env) -> StdResult<StatusResponse> {
     let balance = deps
         .querier
         .query_balance(&_env.contract.address, &config.bond_denom)?;
-    let liquid_supply = get_token_supply(&deps.querier, config.liquid_token_addr)?;
+    let issued = get_token_supply(&deps.querier, &config, &supply)?;
+    let liquid_supply = issued + supply.unstaking_liquid;
 
     let res = StatusResponse {
-        issued: liquid_supply,
+        issued,
         native: coin(supply.native.u128(), &config.bond_denom),
         unstakings: supply.unstakings,
+        unstaking_liquid: supply.unstaking_liquid,
+        rewards: supply.rewards,
+        reward_index: REWARD_INDEX.may_load(deps.storage)?.unwrap_or_default(),
         claims: supply.claims,
         bonded: bonded,
         balance: balance.amount,
@@ -467,13 +2059,16 @@ pub fn query_status(deps: Deps, _env: Env) -> StdResult<StatusResponse> {
         } else {
             Decimal::from_ratio(supply.native, liquid_supply)
         },
+        protocol_fee: config.protocol_fee,
+        fees: supply.fees,
     };
     Ok(res)
 }
 
-pub fn query_unstaking_queue(deps: Deps) -> StdResult<UnstakingQueueResponse> {
-    let state = linked_list_read(deps.storage).load()?;
-    let unstaking_requests: Vec<NodeWithId> = linked_list_get_list(deps.storage, 50)?;
+pub fn query_unstaking_queue(deps: Deps<BindingQuery>, start_after: Option<u64>, limit: Option<u32>) -> StdResult<UnstakingQueueResponse> {
+    let state = LINKED_LIST.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_QUEUE_LIMIT).min(MAX_QUEUE_LIMIT);
+    let unstaking_requests: Vec<NodeWithId> = linked_list_get_list(deps.storage, start_after, limit.into())?;
 
     let res = UnstakingQueueResponse {
         state,
@@ -482,10 +2077,237 @@ pub fn query_unstaking_queue(deps: Deps) -> StdResult<UnstakingQueueResponse> {
     Ok(res)
 }
 
-pub fn query_under_unstaking_of(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+pub fn query_under_unstaking_of(deps: Deps<BindingQuery>, address: String) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let unstaking = UNDER_UNSTAKING
-        .may_load(deps.storage, &address)?
-        .unwrap_or_default();
-    Ok(BalanceResponse { balance: unstaking })
+    let config = CONFIG.load(deps.storage)?;
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    if config.queue_mode == QueueMode::Native {
+        let unstaking = UNDER_UNSTAKING
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default();
+        return Ok(BalanceResponse { balance: units_to_native(&supply, unstaking) });
+    }
+    // liquid requests are worth more or less depending on their epoch
+    let shares = get_liquid_shares(&deps.querier, &config, &supply)?;
+    let mut balance = Uint128::zero();
+    for request in receiver_requests(deps, &address, None, None)? {
+        balance += queued_native(deps.storage, &config, &supply, shares, request.info.epoch, request.info.value)?;
+    }
+    Ok(BalanceResponse { balance })
+}
+
+// receiver_requests returns the queued requests of `address` by node id, following `start_after`
+fn receiver_requests(
+    deps: Deps<BindingQuery>,
+    address: &Addr,
+    start_after: Option<u64>,
+    limit: Option<usize>,
+) -> StdResult<Vec<NodeWithId>> {
+    let ids = RECEIVER_NODES
+        .prefix(address)
+        .keys(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit.unwrap_or(usize::MAX));
+    ids.map(|id| {
+        let id = id?;
+        Ok(NodeWithId { id, info: NODES.load(deps.storage, id)? })
+    })
+    .collect()
+}
+
+pub fn query_unstaking_requests_of(
+    deps: Deps<BindingQuery>,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<UnstakingRequestsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_QUEUE_LIMIT).min(MAX_QUEUE_LIMIT) as usize;
+    let requests = receiver_requests(deps, &address, start_after, Some(limit))?;
+    Ok(UnstakingRequestsResponse { requests })
+}
+
+pub fn query_pending_unbondings_of(deps: Deps<BindingQuery>, env: Env, address: String) -> StdResult<PendingUnbondingsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let shares = get_liquid_shares(&deps.querier, &config, &supply)?;
+    let epoch = CURRENT_EPOCH.load(deps.storage)?;
+
+    let mut unbondings = vec![];
+    for request in receiver_requests(deps, &address, None, None)? {
+        let release_time = match UNDELEGATIONS.may_load(deps.storage, request.info.epoch)? {
+            Some(undelegation) => undelegation.completion,
+            // not undelegated yet, it will be once the current epoch closes
+            None => estimate_release_time(&config, &epoch, env.block.time),
+        };
+        unbondings.push(PendingUnbonding {
+            id: request.id,
+            amount: queued_native(deps.storage, &config, &supply, shares, request.info.epoch, request.info.value)?,
+            epoch: request.info.epoch,
+            release_time,
+            matured: release_time <= env.block.time,
+        });
+    }
+    Ok(PendingUnbondingsResponse { unbondings })
+}
+
+// simulate stake compounds the pending staking rewards first, like Stake does before minting
+pub fn query_simulate_stake(deps: Deps<BindingQuery>, env: Env, amount: Uint128) -> StdResult<SimulateStakeResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let liquid_supply = get_liquid_shares(&deps.querier, &config, &supply)?;
+
+    let mut rewards = Uint128::zero();
+    for (validator, _) in get_delegations(&deps.querier, &env.contract.address)? {
+        if let Some(delegation) = deps.querier.query_delegation(&env.contract.address, validator)? {
+            rewards += delegation
+                .accumulated_rewards
+                .iter()
+                .filter(|reward| reward.denom == config.bond_denom)
+                .map(|reward| reward.amount)
+                .sum::<Uint128>();
+        }
+    }
+    let protocol_fee = rewards.multiply_ratio(config.protocol_fee, FEE_DENOMINATOR);
+    let native = match config.reward_mode {
+        RewardMode::Compound => supply.native + rewards - protocol_fee,
+        // rewards don't change the ratio in index reward mode
+        RewardMode::Index => supply.native,
+    };
+
+    let res = SimulateStakeResponse {
+        minted: native_to_liquid(amount, native, liquid_supply),
+        ratio: if liquid_supply.is_zero() {
+            FALLBACK_RATIO
+        } else {
+            Decimal::from_ratio(native, liquid_supply)
+        },
+    };
+    Ok(res)
+}
+
+pub fn query_simulate_unstake(deps: Deps<BindingQuery>, env: Env, liquid_amount: Uint128) -> StdResult<SimulateUnstakeResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let supply = TOTAL_SUPPLY.load(deps.storage)?;
+    let epoch = CURRENT_EPOCH.load(deps.storage)?;
+    let state = LINKED_LIST.load(deps.storage)?;
+    let issued = get_token_supply(&deps.querier, &config, &supply)?;
+    if issued.is_zero() || liquid_amount > issued {
+        return Err(StdError::generic_err("Amount exceeds the liquid token supply"));
+    }
+    let shares = issued + supply.unstaking_liquid;
+
+    let res = SimulateUnstakeResponse {
+        native_amount: liquid_to_native(liquid_amount, supply.native, shares),
+        queue_position: state.length + 1,
+        queued_ahead: supply.unstakings + queued_native(deps.storage, &config, &supply, shares, epoch.id, supply.unstaking_liquid)?,
+        epoch: epoch.id,
+        release_time: estimate_release_time(&config, &epoch, env.block.time),
+    };
+    Ok(res)
+}
+
+pub fn query_ratio_history(deps: Deps<BindingQuery>, start_after: Option<u64>, limit: Option<u32>) -> StdResult<RatioHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let snapshots = RATIO_SNAPSHOTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, snapshot)| snapshot))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(RatioHistoryResponse { snapshots })
+}
+
+// estimated apr compares the latest snapshot with the oldest one taken within the window
+pub fn query_estimated_apr(deps: Deps<BindingQuery>, window_seconds: u64) -> StdResult<EstimatedAprResponse> {
+    let mut snapshots = RATIO_SNAPSHOTS.range(deps.storage, None, None, Order::Descending);
+    let end = match snapshots.next().transpose()? {
+        Some((_, snapshot)) => snapshot,
+        None => return Ok(EstimatedAprResponse { apr: Decimal::zero(), start: None, end: None }),
+    };
+    let cutoff = end.time.seconds().saturating_sub(window_seconds);
+    let mut start = end.clone();
+    for item in snapshots {
+        let (_, snapshot) = item?;
+        if snapshot.time.seconds() < cutoff {
+            break;
+        }
+        start = snapshot;
+    }
+
+    let elapsed = end.time.seconds() - start.time.seconds();
+    let apr = if elapsed == 0 || start.ratio.is_zero() || end.ratio <= start.ratio {
+        Decimal::zero()
+    } else {
+        (end.ratio - start.ratio) / start.ratio * Decimal::from_ratio(SECONDS_PER_YEAR, elapsed)
+    };
+    Ok(EstimatedAprResponse { apr, start: Some(start), end: Some(end) })
+}
+
+pub fn query_proposal_tally(deps: Deps<BindingQuery>, proposal_id: u64) -> StdResult<ProposalTallyResponse> {
+    let proposal = GOV_PROPOSALS.may_load(deps.storage, proposal_id)?;
+    Ok(ProposalTallyResponse {
+        proposal_id,
+        voting_end: proposal.as_ref().map(|p| p.voting_end),
+        tally: proposal.map(|p| p.tally).unwrap_or_default(),
+    })
+}
+
+pub fn query_vote_of(deps: Deps<BindingQuery>, proposal_id: u64, address: String) -> StdResult<VoteResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let vote = VOTES.may_load(deps.storage, (proposal_id, &address))?;
+    Ok(VoteResponse {
+        weight: vote.as_ref().map(|v| v.weight).unwrap_or_default(),
+        option: vote.map(|v| v.option),
+    })
+}
+
+pub fn query_rewards_of(deps: Deps<BindingQuery>, address: String) -> StdResult<RewardsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let index = REWARD_INDEX.may_load(deps.storage)?.unwrap_or_default();
+    if config.reward_mode != RewardMode::Index {
+        return Ok(RewardsResponse { rewards: Uint128::zero(), index });
+    }
+    let rewards = pending_rewards(deps.storage, &address)?;
+    Ok(RewardsResponse { rewards, index })
+}
+
+pub fn query_voting_power_of(deps: Deps<BindingQuery>, address: String) -> StdResult<VotingPowerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let power = VOTING_POWER.may_load(deps.storage, &address)?.unwrap_or_default();
+    let total = TOTAL_VOTING_POWER.may_load(deps.storage)?.unwrap_or_default();
+    Ok(VotingPowerResponse { power, total })
+}
+
+fn validator_proposal_response(env: &Env, id: u64, proposal: ValidatorProposal) -> ValidatorProposalResponse {
+    ValidatorProposalResponse {
+        id,
+        status: proposal_status(&proposal, env.block.time),
+        executable_at: proposal.voting_end.plus_seconds(proposal.rules.timelock),
+        proposal,
+    }
+}
+
+pub fn query_validator_proposal(deps: Deps<BindingQuery>, env: Env, proposal_id: u64) -> StdResult<ValidatorProposalResponse> {
+    let proposal = VALIDATOR_PROPOSALS.load(deps.storage, proposal_id)?;
+    Ok(validator_proposal_response(&env, proposal_id, proposal))
+}
+
+pub fn query_validator_proposals(
+    deps: Deps<BindingQuery>,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ValidatorProposalsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let proposals = VALIDATOR_PROPOSALS
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id, proposal) = item?;
+            Ok(validator_proposal_response(&env, id, proposal))
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ValidatorProposalsResponse { proposals })
 }
@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+
+use cosmwasm_std::{Addr, Decimal, QuerierWrapper, StdResult, Storage, Timestamp, Uint128};
+
+use crate::bindings::BindingQuery;
+use crate::error::ContractError;
+use crate::state::{ValidatorInfo, REDELEGATIONS};
+
+// MAX_REDELEGATION_ENTRIES mirrors the MaxEntries parameter of the staking module,
+// the chain rejects a redelegation between a pair that already has this many in flight
+pub const MAX_REDELEGATION_ENTRIES: usize = 7;
+// MAX_UNBONDING_ENTRIES is the same limit for the undelegations from a validator
+pub const MAX_UNBONDING_ENTRIES: u64 = 7;
+
+// validate_epoch_period ensures at most one undelegation per epoch stays under the unbonding entry limit,
+// with a margin for an epoch closing in the same block as an older undelegation matures
+pub fn validate_epoch_period(epoch_period: u64, unbonding_period: u64) -> Result<(), ContractError> {
+    if epoch_period * MAX_UNBONDING_ENTRIES <= unbonding_period {
+        return Err(ContractError::InvalidEpochPeriod {
+            min: unbonding_period / MAX_UNBONDING_ENTRIES + 1,
+        });
+    }
+    Ok(())
+}
+
+// validate_validators ensures the set is not empty, has no duplicates and only
+// contains validators known to the staking module
+pub fn validate_validators(
+    querier: &QuerierWrapper<BindingQuery>,
+    validators: &[ValidatorInfo],
+) -> Result<(), ContractError> {
+    if validators.iter().all(|v| v.weight == 0) {
+        return Err(ContractError::EmptyValidatorSet {});
+    }
+    let mut seen = HashSet::new();
+    for validator in validators {
+        if !seen.insert(validator.address.as_str()) {
+            return Err(ContractError::DuplicateValidator {
+                validator: validator.address.clone(),
+            });
+        }
+        // address validation doesn't work for validator addresses, so ask the chain instead
+        if querier.query_validator(validator.address.clone())?.is_none() {
+            return Err(ContractError::UnknownValidator {
+                validator: validator.address.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+// get_delegations returns the amount currently delegated to each validator by the contract
+pub fn get_delegations(querier: &QuerierWrapper<BindingQuery>, contract: &Addr) -> StdResult<Vec<(String, Uint128)>> {
+    let delegations = querier
+        .query_all_delegations(contract)?
+        .into_iter()
+        .map(|d| (d.validator, d.amount.amount))
+        .collect();
+    Ok(delegations)
+}
+
+fn delegated_to(delegations: &[(String, Uint128)], validator: &str) -> Uint128 {
+    delegations
+        .iter()
+        .find(|(v, _)| v == validator)
+        .map(|(_, amount)| *amount)
+        .unwrap_or_default()
+}
+
+// target_delegations returns how much each validator in the set should hold when `total` is bonded
+pub fn target_delegations(validators: &[ValidatorInfo], total: Uint128) -> Vec<(String, Uint128)> {
+    let total_weight: u64 = validators.iter().map(|v| v.weight).sum();
+    validators
+        .iter()
+        .map(|v| {
+            let target = if total_weight == 0 {
+                Uint128::zero()
+            } else {
+                total.multiply_ratio(v.weight, total_weight)
+            };
+            (v.address.clone(), target)
+        })
+        .collect()
+}
+
+// split_delegation spreads a new delegation of `amount` over the validator set,
+// filling the validators that are furthest below their target first
+pub fn split_delegation(
+    validators: &[ValidatorInfo],
+    delegations: &[(String, Uint128)],
+    amount: Uint128,
+) -> Vec<(String, Uint128)> {
+    let bonded: Uint128 = delegations.iter().map(|(_, amount)| *amount).sum();
+    let deficits: Vec<(String, Uint128)> = target_delegations(validators, bonded + amount)
+        .into_iter()
+        .map(|(v, target)| {
+            let current = delegated_to(delegations, &v);
+            (v, target.saturating_sub(current))
+        })
+        .collect();
+    if deficits.iter().all(|(_, deficit)| deficit.is_zero()) {
+        // every validator is already at its target, follow the weights
+        let weights: Vec<(String, Uint128)> = validators
+            .iter()
+            .map(|v| (v.address.clone(), Uint128::from(v.weight)))
+            .collect();
+        return allocate(amount, &weights);
+    }
+    allocate(amount, &deficits)
+}
+
+// split_undelegation picks the validators to unbond `amount` from, taking first from the
+// validators that are furthest above their target (validators removed from the set have no target)
+pub fn split_undelegation(
+    validators: &[ValidatorInfo],
+    delegations: &[(String, Uint128)],
+    amount: Uint128,
+) -> Vec<(String, Uint128)> {
+    let bonded: Uint128 = delegations.iter().map(|(_, amount)| *amount).sum();
+    let amount = amount.min(bonded);
+    let targets = target_delegations(validators, bonded - amount);
+    let excesses: Vec<(String, Uint128)> = delegations
+        .iter()
+        .map(|(v, current)| {
+            let target = delegated_to(&targets, v);
+            (v.clone(), current.saturating_sub(target))
+        })
+        .collect();
+    allocate(amount, &excesses)
+}
+
+// allocate splits `amount` proportionally to `shares`. The rounding remainder goes to the entries
+// with room left under their share, so the result never exceeds a share while the total allows it.
+// Zero allocations are dropped.
+fn allocate(amount: Uint128, shares: &[(String, Uint128)]) -> Vec<(String, Uint128)> {
+    let total: Uint128 = shares.iter().map(|(_, share)| *share).sum();
+    if total.is_zero() || amount.is_zero() {
+        return vec![];
+    }
+    let mut allocations: Vec<(String, Uint128)> = shares
+        .iter()
+        .map(|(v, share)| (v.clone(), amount.multiply_ratio(*share, total)))
+        .collect();
+    let allocated: Uint128 = allocations.iter().map(|(_, part)| *part).sum();
+    let mut remaining = amount - allocated;
+    for ((_, part), (_, share)) in allocations.iter_mut().zip(shares.iter()) {
+        if remaining.is_zero() {
+            break;
+        }
+        let extra = share.saturating_sub(*part).min(remaining);
+        *part += extra;
+        remaining -= extra;
+    }
+    if !remaining.is_zero() {
+        allocations[0].1 += remaining;
+    }
+    allocations.retain(|(_, part)| !part.is_zero());
+    allocations
+}
+
+// redelegatable returns how much of the delegation to `validator` the chain allows to be redelegated now,
+// stake that arrived through a redelegation that is still maturing cannot be moved again
+pub fn redelegatable(querier: &QuerierWrapper<BindingQuery>, contract: &Addr, validator: &str) -> StdResult<Uint128> {
+    let delegation = querier.query_delegation(contract, validator)?;
+    Ok(delegation.map(|d| d.can_redelegate.amount).unwrap_or_default())
+}
+
+// redelegation_entries returns the completion times of the redelegations from `src` to `dst`
+// that have not matured yet
+pub fn redelegation_entries(
+    storage: &dyn Storage,
+    src: &str,
+    dst: &str,
+    now: Timestamp,
+) -> StdResult<Vec<Timestamp>> {
+    let mut entries = REDELEGATIONS.may_load(storage, (src, dst))?.unwrap_or_default();
+    entries.retain(|completion| *completion > now);
+    Ok(entries)
+}
+
+// record_redelegation stores a new redelegation entry, dropping the matured ones
+pub fn record_redelegation(
+    storage: &mut dyn Storage,
+    src: &str,
+    dst: &str,
+    now: Timestamp,
+    completion: Timestamp,
+) -> StdResult<()> {
+    let mut entries = redelegation_entries(storage, src, dst, now)?;
+    entries.push(completion);
+    REDELEGATIONS.save(storage, (src, dst), &entries)
+}
+
+// rebalance_moves returns the redelegations (src, dst, amount) that bring the delegations back
+// toward their targets, when any validator drifted past `threshold`. Moves are limited by what the
+// chain allows to be redelegated and by the number of redelegation entries in flight.
+pub fn rebalance_moves(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper<BindingQuery>,
+    contract: &Addr,
+    validators: &[ValidatorInfo],
+    threshold: Decimal,
+    now: Timestamp,
+) -> StdResult<Vec<(String, String, Uint128)>> {
+    let mut moves = vec![];
+    if threshold.is_zero() {
+        return Ok(moves);
+    }
+    let delegations = get_delegations(querier, contract)?;
+    let bonded: Uint128 = delegations.iter().map(|(_, amount)| *amount).sum();
+    if bonded.is_zero() {
+        return Ok(moves);
+    }
+    let targets = target_delegations(validators, bonded);
+    let max_drift = bonded * threshold;
+
+    // validators above their target, including the ones removed from the set
+    let mut sources: Vec<(String, Uint128)> = delegations
+        .iter()
+        .map(|(v, current)| (v.clone(), current.saturating_sub(delegated_to(&targets, v))))
+        .filter(|(_, excess)| !excess.is_zero())
+        .collect();
+    // validators below their target
+    let mut sinks: Vec<(String, Uint128)> = targets
+        .iter()
+        .map(|(v, target)| (v.clone(), target.saturating_sub(delegated_to(&delegations, v))))
+        .filter(|(_, deficit)| !deficit.is_zero())
+        .collect();
+    let drifted = sources.iter().chain(sinks.iter()).any(|(_, drift)| *drift > max_drift);
+    if !drifted {
+        return Ok(moves);
+    }
+    sources.sort_by_key(|(_, excess)| std::cmp::Reverse(*excess));
+    sinks.sort_by_key(|(_, deficit)| std::cmp::Reverse(*deficit));
+
+    for (src, excess) in sources {
+        let mut movable = excess.min(redelegatable(querier, contract, &src)?);
+        for (dst, deficit) in sinks.iter_mut() {
+            if movable.is_zero() {
+                break;
+            }
+            if deficit.is_zero()
+                || redelegation_entries(storage, &src, dst, now)?.len() >= MAX_REDELEGATION_ENTRIES
+            {
+                continue;
+            }
+            let amount = movable.min(*deficit);
+            movable -= amount;
+            *deficit -= amount;
+            moves.push((src.clone(), dst.clone(), amount));
+        }
+    }
+    Ok(moves)
+}
@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp, Uint128};
+use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +7,15 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
     
+    #[error("{0}")]
+    SemVer(#[from] semver::Error),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unauthorized")]
     Unauthorized {},
     // Add any other custom errors you like here.
@@ -20,6 +30,141 @@ pub enum ContractError {
     #[error("No claims that can be released currently")]
     NothingToClaim {},
 
+    #[error("Stake would exceed the TVL cap of {cap}")]
+    TvlCapExceeded { cap: Uint128 },
+
+    #[error("Stake must be at least {min}")]
+    StakeBelowMinimum { min: Uint128 },
+
+    #[error("Unstake must be worth at least {min} native tokens")]
+    UnstakeBelowMinimum { min: Uint128 },
+
+    #[error("Stake would exceed the limit of {limit} per address")]
+    AddressStakeLimitExceeded { limit: Uint128 },
+
+    #[error("Unstake request {id} not found")]
+    UnstakeRequestNotFound { id: u64 },
+
+    #[error("No ownership transfer is pending")]
+    NoOwnershipProposal {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
+    #[error("No Archway rewards to withdraw")]
+    NoArchwayRewards {},
+
+    #[error("Liquid token is not a token-factory denom")]
+    NoLiquidDenom {},
+
+    #[error("Liquid tokens must be sent with a ReceiveMsg")]
+    EmptyReceiveMsg {},
+
+    #[error("Invalid ReceiveMsg: {reason}")]
+    InvalidReceiveMsg { reason: String },
+
+    #[error("No swap contract is set for instant unstakes")]
+    NoSwapContract {},
+
+    #[error("Instant unstake would pay {amount}, less than the minimum {min}")]
+    InstantUnstakeBelowMinimum { amount: Uint128, min: Uint128 },
+
+    #[error("No liquid tokens to vote with")]
+    NoVotingPower {},
+
+    #[error("Governance proposal {proposal_id} is not registered")]
+    GovProposalNotFound { proposal_id: u64 },
+
+    #[error("Governance proposal {proposal_id} is already registered")]
+    GovProposalRegistered { proposal_id: u64 },
+
+    #[error("Voting on governance proposal {proposal_id} is closed")]
+    GovVotingClosed { proposal_id: u64 },
+
+    #[error("At most {max} governance proposals can be open at once")]
+    TooManyOpenProposals { max: u64 },
+
+    #[error("No votes to cast on proposal {proposal_id}")]
+    NoVotes { proposal_id: u64 },
+
+    #[error("Only {available} liquid tokens are locked")]
+    InsufficientVotingPower { available: Uint128 },
+
+    #[error("Validator proposals are disabled")]
+    ValidatorGovDisabled {},
+
+    #[error("Quorum and threshold of validator proposals can't be more than one")]
+    InvalidValidatorGov {},
+
+    #[error("Validator proposal must change the validator set")]
+    EmptyValidatorProposal {},
+
+    #[error("Validator proposal {id} not found")]
+    ValidatorProposalNotFound { id: u64 },
+
+    #[error("Voting on validator proposal {id} is closed")]
+    VotingClosed { id: u64 },
+
+    #[error("Validator proposal {id} has not passed")]
+    ValidatorProposalNotPassed { id: u64 },
+
+    #[error("Validator proposal {id} was already executed")]
+    ValidatorProposalExecuted { id: u64 },
+
+    #[error("Validator proposal can't be executed before {time}")]
+    TimelockNotExpired { time: Timestamp },
+
+    #[error("Rewards are compounded into the exchange rate, not claimed")]
+    NoRewardIndex {},
+
+    #[error("No rewards to claim")]
+    NoRewards {},
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Can't migrate from contract '{contract}'")]
+    InvalidMigrationContract { contract: String },
+
+    #[error("Can't migrate from version {stored} to older version {version}")]
+    CannotDowngrade { stored: String, version: String },
+
+    #[error("Validator set must contain at least one validator with a non-zero weight")]
+    EmptyValidatorSet {},
+
+    #[error("Validator '{validator}' is listed more than once")]
+    DuplicateValidator { validator: String },
+
+    #[error("Validator '{validator}' does not exist")]
+    UnknownValidator { validator: String },
+
+    #[error("Validator '{validator}' is not in the validator set")]
+    ValidatorNotInSet { validator: String },
+
+    #[error("Only {available} tokens can be redelegated from '{validator}' right now")]
+    RedelegationUnavailable { validator: String, available: Uint128 },
+
+    #[error("Can't redelegate zero tokens")]
+    ZeroRedelegation {},
+
+    #[error("Too many redelegations in progress from '{src}' to '{dst}'")]
+    TooManyRedelegations { src: String, dst: String },
+
+    #[error("Epoch period must be at least {min} seconds")]
+    InvalidEpochPeriod { min: u64 },
+
+    #[error("Protocol fee can't be more than {max} basis points")]
+    InvalidProtocolFee { max: u64 },
+
+    #[error("Protocol fee and harvest bounty can't be more than {max} basis points")]
+    InvalidHarvestBounty { max: u64 },
+
+    #[error("Too soon to harvest, next harvest is allowed at height {height}")]
+    HarvestTooSoon { height: u64 },
+
+    #[error("{operation} is paused")]
+    Paused { operation: String },
+
     // #[error("Balance should be zero but: '{balance}'")]
     // BalanceShouldBeZero { balance: String },
 }
@@ -0,0 +1,142 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Decimal, Timestamp, Uint128, VoteOption, WeightedVoteOption};
+
+use crate::error::ContractError;
+use crate::state::{ValidatorChange, ValidatorGovConfig, ValidatorInfo, ValidatorProposal};
+
+// Tally is the weight of the holders who voted on a proposal, by option
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Tally {
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub abstain: Uint128,
+    pub no_with_veto: Uint128,
+    /// number of holders who voted
+    pub voters: u64,
+}
+
+impl Tally {
+    pub fn add(&mut self, option: &VoteOption, weight: Uint128) {
+        match option {
+            VoteOption::Yes => self.yes += weight,
+            VoteOption::No => self.no += weight,
+            VoteOption::Abstain => self.abstain += weight,
+            VoteOption::NoWithVeto => self.no_with_veto += weight,
+        }
+        self.voters += 1;
+    }
+
+    pub fn remove(&mut self, option: &VoteOption, weight: Uint128) {
+        match option {
+            VoteOption::Yes => self.yes -= weight,
+            VoteOption::No => self.no -= weight,
+            VoteOption::Abstain => self.abstain -= weight,
+            VoteOption::NoWithVeto => self.no_with_veto -= weight,
+        }
+        self.voters -= 1;
+    }
+
+    pub fn total(&self) -> Uint128 {
+        self.yes + self.no + self.abstain + self.no_with_veto
+    }
+}
+
+// weighted_options splits the vote of the contract according to the tally. Options nobody voted for are
+// left out and the last option gets the rounding, the weights must add up to exactly one.
+pub fn weighted_options(tally: &Tally) -> Vec<WeightedVoteOption> {
+    let total = tally.total();
+    let votes: Vec<(VoteOption, Uint128)> = vec![
+        (VoteOption::Yes, tally.yes),
+        (VoteOption::No, tally.no),
+        (VoteOption::Abstain, tally.abstain),
+        (VoteOption::NoWithVeto, tally.no_with_veto),
+    ]
+    .into_iter()
+    .filter(|(_, amount)| !amount.is_zero())
+    .collect();
+
+    let mut options = vec![];
+    let mut remaining = Decimal::one();
+    for (i, (option, amount)) in votes.iter().enumerate() {
+        let weight = if i + 1 == votes.len() {
+            remaining
+        } else {
+            Decimal::from_ratio(*amount, total)
+        };
+        remaining -= weight;
+        options.push(WeightedVoteOption { option: option.clone(), weight });
+    }
+    options
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    /// Open for voting
+    Open,
+    /// Passed, the changes can be executed once the timelock expired
+    Passed,
+    /// Rejected or quorum not reached
+    Rejected,
+    /// Executed, the changes were applied to the validator set
+    Executed,
+}
+
+pub fn validate_validator_gov(validator_gov: &ValidatorGovConfig) -> Result<(), ContractError> {
+    if validator_gov.quorum > Decimal::one() || validator_gov.threshold > Decimal::one() {
+        return Err(ContractError::InvalidValidatorGov {});
+    }
+    Ok(())
+}
+
+// apply_validator_changes returns the validator set after the changes of a validator proposal,
+// the result still has to go through validate_validators
+pub fn apply_validator_changes(
+    validators: &[ValidatorInfo],
+    changes: &[ValidatorChange],
+) -> Result<Vec<ValidatorInfo>, ContractError> {
+    let mut validators = validators.to_vec();
+    for change in changes {
+        match change {
+            ValidatorChange::Add { address, weight } => validators.push(ValidatorInfo {
+                address: address.clone(),
+                weight: *weight,
+            }),
+            ValidatorChange::Remove { address } => {
+                let position = validators
+                    .iter()
+                    .position(|v| &v.address == address)
+                    .ok_or_else(|| ContractError::ValidatorNotInSet { validator: address.clone() })?;
+                validators.remove(position);
+            }
+            ValidatorChange::SetWeight { address, weight } => {
+                let validator = validators
+                    .iter_mut()
+                    .find(|v| &v.address == address)
+                    .ok_or_else(|| ContractError::ValidatorNotInSet { validator: address.clone() })?;
+                validator.weight = *weight;
+            }
+        }
+    }
+    Ok(validators)
+}
+
+// proposal_status tells whether a validator proposal passed. It needs the quorum of the liquid tokens
+// issued to vote, and more than the threshold of the yes and no votes to be yes.
+pub fn proposal_status(proposal: &ValidatorProposal, now: Timestamp) -> ProposalStatus {
+    if proposal.executed {
+        return ProposalStatus::Executed;
+    }
+    if now < proposal.voting_end {
+        return ProposalStatus::Open;
+    }
+    let votes = proposal.yes + proposal.no + proposal.abstain;
+    let quorum_reached = !votes.is_zero() && votes >= proposal.total_power * proposal.rules.quorum;
+    if quorum_reached && proposal.yes > (proposal.yes + proposal.no) * proposal.rules.threshold {
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    }
+}
@@ -1,7 +1,16 @@
+pub mod bindings;
 pub mod contract;
+pub mod delegation;
+pub mod governance;
 pub mod linked_list;
 mod error;
+pub mod migration;
 pub mod msg;
+pub mod rewards;
+pub mod slashing;
 pub mod state;
 
+#[cfg(test)]
+mod testing;
+
 pub use crate::error::ContractError;
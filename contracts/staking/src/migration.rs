@@ -0,0 +1,164 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{coin, Addr, Decimal, DepsMut, Env, StakingMsg, StdResult, Uint128};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read};
+use cw_storage_plus::Item;
+
+use crate::bindings::BindingQuery;
+use crate::delegation::{get_delegations, split_undelegation};
+use crate::linked_list::{LinkedList, Node, LINKED_LIST, NODES, RECEIVER_NODES};
+use crate::state::{ConfigInfo, Epoch, PauseInfo, QueueMode, RewardMode, Supply, Undelegation, ValidatorInfo, CONFIG, CURRENT_EPOCH,
+    TOTAL_SUPPLY, UNDELEGATIONS};
+
+// unbonding period of the chain, 21 days
+const DEFAULT_UNBONDING_PERIOD: u64 = 21 * 24 * 60 * 60;
+// keeps the undelegations of a validator under the 7 unbonding entries allowed by the chain,
+// with room for an epoch closing in the same block as an older undelegation matures
+const DEFAULT_EPOCH_PERIOD: u64 = DEFAULT_UNBONDING_PERIOD / 6;
+
+// v0.1.0 stored the unstaking queue with cosmwasm_storage
+static LEGACY_NODE_KEY: &[u8] = b"node";
+static LEGACY_LINKED_LIST_KEY: &[u8] = b"linked_list";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct ConfigInfoV0_1 {
+    owner: Addr,
+    bond_denom: String,
+    liquid_token_addr: Addr,
+    validator: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct SupplyV0_1 {
+    native: Uint128,
+    unstakings: Uint128,
+    claims: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct NodeV0_1 {
+    receiver: Addr,
+    value: Uint128,
+    height: u64,
+    prev: u64,
+    next: u64,
+}
+
+const CONFIG_V0_1: Item<ConfigInfoV0_1> = Item::new("config");
+const TOTAL_SUPPLY_V0_1: Item<SupplyV0_1> = Item::new("total_supply");
+
+// migrate_from_v0_1 upgrades the state written by v0.1.0 and returns the undelegations funding
+// the requests left in its queue
+pub fn migrate_from_v0_1(deps: DepsMut<BindingQuery>, env: &Env) -> StdResult<Vec<StakingMsg>> {
+    let storage = deps.storage;
+    // config: single validator becomes a set of one, new settings get their defaults
+    let legacy_config = CONFIG_V0_1.load(storage)?;
+    let config = ConfigInfo {
+        treasury: legacy_config.owner.clone(),
+        owner: legacy_config.owner,
+        bond_denom: legacy_config.bond_denom,
+        liquid_token_addr: legacy_config.liquid_token_addr,
+        liquid_denom: None,
+        validators: vec![ValidatorInfo {
+            address: legacy_config.validator,
+            weight: 1,
+        }],
+        unbonding_period: DEFAULT_UNBONDING_PERIOD,
+        rebalance_threshold: Decimal::zero(),
+        epoch_period: DEFAULT_EPOCH_PERIOD,
+        protocol_fee: 0,
+        harvest_bounty: 0,
+        harvest_interval: 0,
+        max_tvl: None,
+        min_stake: Uint128::zero(),
+        min_unstake: Uint128::zero(),
+        max_stake_per_address: None,
+        swap_contract: None,
+        guardian: None,
+        paused: PauseInfo::default(),
+        validator_gov: None,
+        queue_mode: QueueMode::Native,
+        reward_mode: RewardMode::Compound,
+    };
+    CONFIG.save(storage, &config)?;
+
+    let legacy_supply = TOTAL_SUPPLY_V0_1.load(storage)?;
+    let supply = Supply {
+        native: legacy_supply.native,
+        unstakings: legacy_supply.unstakings,
+        // the queued requests hold one unit per native token they are owed
+        unstaking_units: legacy_supply.unstakings,
+        claims: legacy_supply.claims,
+        fees: Uint128::zero(),
+        issued: Uint128::zero(),
+        unstaking_liquid: Uint128::zero(),
+        rewards: Uint128::zero(),
+        peg_shortfall: Uint128::zero(),
+    };
+    TOTAL_SUPPLY.save(storage, &supply)?;
+
+    // the undelegations v0.1.0 has in flight land at unknown times and can't be held for the queue,
+    // so the queued requests become epoch 0, funded by a real undelegation of everything they are
+    // owed. The v0.1.0 tokens still in flight, owed but neither bonded beyond the native supply nor
+    // held free, count as undelegated until epoch 0 completes and are delegated again once landed.
+    let mut msgs = vec![];
+    if !supply.unstakings.is_zero() {
+        let delegations = get_delegations(&deps.querier, &env.contract.address)?;
+        let bonded: Uint128 = delegations.iter().map(|(_, amount)| *amount).sum();
+        let balance = deps.querier.query_balance(&env.contract.address, &config.bond_denom)?.amount;
+        let free = balance.saturating_sub(supply.claims);
+        let uncovered = bonded.saturating_sub(supply.native).min(supply.unstakings);
+        let legacy_in_flight = supply.unstakings.saturating_sub(uncovered + free);
+
+        let mut unstake_amount = Uint128::zero();
+        for (validator, amount) in split_undelegation(&config.validators, &delegations, supply.unstakings) {
+            unstake_amount += amount;
+            msgs.push(StakingMsg::Undelegate {
+                validator,
+                amount: coin(amount.u128(), &config.bond_denom),
+            });
+        }
+        let undelegation = Undelegation {
+            amount: unstake_amount + legacy_in_flight,
+            time: env.block.time,
+            completion: env.block.time.plus_seconds(config.unbonding_period),
+            matured_at: None,
+            liquid: Uint128::zero(),
+            units: supply.unstaking_units,
+        };
+        UNDELEGATIONS.save(storage, 0, &undelegation)?;
+    }
+    let epoch = Epoch {
+        id: 1,
+        start: env.block.time,
+        pending: Uint128::zero(),
+    };
+    CURRENT_EPOCH.save(storage, &epoch)?;
+
+    // unstaking queue: move the cosmwasm_storage buckets into cw-storage-plus maps
+    let mut state: LinkedList = singleton_read(storage, LEGACY_LINKED_LIST_KEY).load()?;
+    // v0.1.0 appended after the tail, ids past it were never handed out
+    state.last_id = state.tail_id;
+    let mut node_id = state.head_id;
+    for _ in 0..state.length {
+        let key = node_id.to_be_bytes();
+        let legacy_node: NodeV0_1 = bucket_read(storage, LEGACY_NODE_KEY).load(&key)?;
+        let node = Node {
+            receiver: legacy_node.receiver,
+            value: legacy_node.value,
+            height: legacy_node.height,
+            epoch: 0,
+            prev: legacy_node.prev,
+            next: legacy_node.next,
+        };
+        NODES.save(storage, node_id, &node)?;
+        RECEIVER_NODES.save(storage, (&node.receiver, node_id), &node.value)?;
+        bucket::<NodeV0_1>(storage, LEGACY_NODE_KEY).remove(&key);
+        node_id = legacy_node.next;
+    }
+    LINKED_LIST.save(storage, &state)?;
+    singleton::<LinkedList>(storage, LEGACY_LINKED_LIST_KEY).remove();
+
+    Ok(msgs)
+}
@@ -1,35 +1,182 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128, Decimal, Coin};
-use cw20::{Cw20ReceiveMsg};
+use cosmwasm_std::{Uint128, Decimal, Coin, Timestamp, VoteOption};
+use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse};
 
+use crate::governance::{ProposalStatus, Tally};
 use crate::linked_list::{NodeWithId, LinkedList};
+use crate::state::{Ballot, PauseInfo, QueueMode, RatioSnapshot, RewardMode, ValidatorChange, ValidatorGovConfig, ValidatorInfo, ValidatorProposal};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    /// This is the liquid token contract address
-    // pub liquid_token_addr: Addr,
-    /// This is the validator that all tokens will be bonded to
-    pub validator: String,
+    /// Liquid token to create, a cw20 contract or a token-factory denom
+    pub liquid_token: LiquidTokenInfo,
+    /// These are the validators that all tokens will be bonded to, with their target weights
+    pub validators: Vec<ValidatorInfo>,
+    /// Unbonding period of the chain in seconds
+    pub unbonding_period: u64,
+    /// Drift from the target weights (as a share of the total bonded) that triggers a rebalance
+    pub rebalance_threshold: Decimal,
+    /// Minimum number of seconds between two undelegations
+    pub epoch_period: u64,
+    /// Share of the staking rewards taken as protocol fee, in basis points
+    pub protocol_fee: u64,
+    /// Protocol fees are sent to this address
+    pub treasury: String,
+    /// Share of the harvested staking rewards paid to the caller of Harvest, in basis points
+    pub harvest_bounty: u64,
+    /// Minimum number of blocks between two harvests
+    pub harvest_interval: u64,
+    /// Native tokens liquid token holders can withdraw are capped to this amount
+    pub max_tvl: Option<Uint128>,
+    /// Minimum amount of native tokens per stake
+    pub min_stake: Uint128,
+    /// Minimum amount of native tokens per unstake request
+    pub min_unstake: Uint128,
+    /// Native tokens a single address can hold in liquid tokens are capped to this amount
+    pub max_stake_per_address: Option<Uint128>,
+    /// Swap contract used for instant unstakes
+    pub swap_contract: Option<String>,
+    /// Guardian can pause operations during an incident
+    pub guardian: Option<String>,
+    /// Liquid token holders can change the validator set through proposals when set
+    pub validator_gov: Option<ValidatorGovConfig>,
+    /// Unit of the unstake requests waiting in the queue, it can't be changed later
+    pub queue_mode: QueueMode,
+    /// How the staking rewards reach the liquid token holders, it can't be changed later
+    pub reward_mode: RewardMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidTokenInfo {
+    /// Cw20 instantiates a cw20-base contract with code id `code_id` as liquid token
+    Cw20 { code_id: u64, name: String, symbol: String, decimals: u8 },
+    /// TokenFactory creates the token-factory denom `factory/{contract}/{subdenom}` as liquid token
+    TokenFactory { subdenom: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     /// Stake will stake and delegate all native tokens sent with the message and give back stkTokens
-    Stake {},
-    /// Claim is used to claim the amount of available native tokens that you previously "unstaked" 
-    Claim {},
-    /// Admin call this method to set up liquid token address 
-    SetLiquidToken { address: Addr },
+    /// to the recipient (the sender by default)
+    Stake { recipient: Option<String> },
+    /// Claim is used to claim the amount of available native tokens that you previously "unstaked",
+    /// they are sent to the recipient (the sender by default)
+    Claim { recipient: Option<String> },
+    /// Unstake queues an unstake request of the liquid token denom sent with the message, paid to the
+    /// recipient (the sender by default). Only with a token-factory liquid token, cw20 liquid tokens are
+    /// unstaked through Receive
+    Unstake { recipient: Option<String> },
+    /// CancelUnstake removes a queued unstake request of the caller, the part not paid out yet
+    /// is staked again and liquid tokens are minted back at the current ratio. Not allowed while
+    /// unstake or stake is paused
+    CancelUnstake { request_id: u64 },
+    /// WithdrawArchwayRewards withdraws the Archway gas rebates and premiums of this contract and
+    /// adds them to the native tokens of liquid token holders, anyone can call it
+    WithdrawArchwayRewards {},
+    /// Harvest compounds the staking rewards and processes the unstaking queue, anyone can call it
+    /// and is paid the harvest bounty out of the rewards
+    Harvest {},
+    /// ClaimRewards sends the native tokens of rewards accrued by the liquid tokens the caller locked as
+    /// voting power, in index reward mode
+    ClaimRewards {},
+    /// UpdateIndex checkpoints the rewards accrued by the liquid tokens the caller locked, in index reward mode
+    UpdateIndex {},
+    /// Vote records the vote of the caller on a registered governance proposal, weighted by the liquid
+    /// tokens it locked as voting power before the proposal was registered. Voting again replaces the
+    /// previous vote
+    Vote { proposal_id: u64, option: VoteOption },
+    /// CastVote votes on the proposal for the contract, split according to the votes of the liquid
+    /// token holders. Anyone can call it, as often as needed before the end of the voting period
+    CastVote { proposal_id: u64 },
+    /// LockVotingPower locks the liquid token denom sent with the message as voting power on validator
+    /// proposals, they earn rewards in index reward mode. Only with a token-factory liquid token,
+    /// cw20 liquid tokens are locked through Receive
+    LockVotingPower {},
+    /// UnlockVotingPower sends back `amount` of the liquid tokens locked by the caller
+    UnlockVotingPower { amount: Uint128 },
+    /// ProposeValidatorChanges opens a vote of the liquid token holders on changes to the validator set,
+    /// the caller must have locked voting power
+    ProposeValidatorChanges { changes: Vec<ValidatorChange> },
+    /// VoteValidatorProposal votes on a validator proposal with the voting power locked by the caller
+    /// when the proposal was created. Voting again replaces the previous vote
+    VoteValidatorProposal { proposal_id: u64, ballot: Ballot },
+    /// ExecuteValidatorProposal applies a passed validator proposal once its timelock expired,
+    /// anyone can call it
+    ExecuteValidatorProposal { proposal_id: u64 },
+    /// Admin call this method to replace the validator set and their target weights
+    UpdateValidators { validators: Vec<ValidatorInfo> },
+    /// Admin call this method to move stake from one validator to another, the rewards the chain
+    /// withdraws from both validators are compounded like harvested ones
+    Redelegate { src: String, dst: String, amount: Uint128 },
+    /// Admin call this method to set the drift that triggers an automatic rebalance
+    SetRebalanceThreshold { threshold: Decimal },
+    /// Admin call this method to set the protocol fee (in basis points) and the treasury receiving it
+    SetProtocolFee { protocol_fee: u64, treasury: String },
+    /// Admin call this method to set the harvest bounty (in basis points) and the minimum blocks between harvests
+    SetHarvestBounty { harvest_bounty: u64, harvest_interval: u64 },
+    /// Admin call this method to set the TVL cap, the minimum stake and unstake amounts
+    /// and the stake cap per address
+    SetStakeLimits {
+        max_tvl: Option<Uint128>,
+        min_stake: Uint128,
+        min_unstake: Uint128,
+        max_stake_per_address: Option<Uint128>,
+    },
+    /// Admin call this method to set (or remove) the swap contract used for instant unstakes
+    SetSwapContract { swap_contract: Option<String> },
+    /// Admin call this method to open a governance proposal of the chain to the votes of liquid token
+    /// holders until `voting_end`, the end of its voting period. The voting power is taken at the start
+    /// of this block
+    RegisterProposal { proposal_id: u64, voting_end: Timestamp },
+    /// Admin call this method to set (or disable) the validator proposals of liquid token holders
+    SetValidatorGov { validator_gov: Option<ValidatorGovConfig> },
+    /// Admin call this method to set (or remove) the guardian
+    SetGuardian { guardian: Option<String> },
+    /// Guardian or admin call this method to pause the selected operations
+    Pause { stake: bool, unstake: bool, claim: bool },
+    /// Admin call this method to unpause the selected operations
+    Unpause { stake: bool, unstake: bool, claim: bool },
+    /// Admin call this method to propose a new owner, the proposal can expire after `expires_in` seconds
+    ProposeNewOwner { owner: String, expires_in: Option<u64> },
+    /// Proposed owner call this method to become the owner
+    AcceptOwnership {},
+    /// Admin call this method to cancel the pending ownership proposal
+    CancelOwnershipProposal {},
 
     /// This accepts a properly-encoded ReceiveMsg from a cw20 contract (to process unstake request)
     Receive(Cw20ReceiveMsg),
+}
+
+/// ReceiveMsg is the payload of the liquid tokens sent to this contract through the cw20 Send
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Unstake queues an unstake request of the liquid tokens sent, paid to the recipient
+    /// (the sender by default)
+    Unstake { recipient: Option<String> },
+    /// InstantUnstake swaps the liquid tokens sent for native tokens through the swap contract,
+    /// failing if less than `min_native` would be received
+    InstantUnstake { min_native: Uint128, recipient: Option<String> },
+    /// LockVotingPower locks the liquid tokens sent as voting power on validator proposals,
+    /// they earn rewards in index reward mode
+    LockVotingPower {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
 
-    _ProcessToken { balance_before: Uint128 },
-    _PerformCheck {},
-    _MintLiquidToken { receiver: Addr, native_amount: Uint128 },
+/// TokenInstantiateMsg is the instantiate message of the cw20-base liquid token contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenInstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -41,10 +188,38 @@ pub enum QueryMsg {
     ConfigInfo {},
     /// StatusInfo shows staking info of the contract
     StatusInfo {},
-    /// UnstakingQueue shows first 50 nodes in the unstaking queue of the contract
-    UnstakingQueue {},
-    /// UnderUnstaking shows the total number of native tokens this address is waiting to be unstaked
+    /// UnstakingQueue shows the nodes in the unstaking queue of the contract, from the head or following
+    /// the node `start_after`. Their values are in liquid tokens in liquid queue mode
+    UnstakingQueue { start_after: Option<u64>, limit: Option<u32> },
+    /// UnstakingRequestsOf shows the queued unstake requests of this address, by node id
+    UnstakingRequestsOf { address: String, start_after: Option<u64>, limit: Option<u32> },
+    /// UnderUnstaking shows the total number of native tokens this address is waiting to be unstaked,
+    /// at the current ratio for the requests of the open epoch in liquid queue mode
     UnderUnstakingOf { address: String },
+    /// PendingUnbondingsOf shows the queued unstake requests of this address with their estimated release time
+    PendingUnbondingsOf { address: String },
+    /// RatioHistory shows the exchange rate snapshots taken every time the rewards were compounded,
+    /// oldest first
+    RatioHistory { start_after: Option<u64>, limit: Option<u32> },
+    /// SimulateStake shows the liquid tokens minted when staking `amount` native tokens now
+    SimulateStake { amount: Uint128 },
+    /// SimulateUnstake shows the native tokens queued when unstaking `liquid_amount` liquid tokens now,
+    /// with the estimated position in the unstaking queue and claim time
+    SimulateUnstake { liquid_amount: Uint128 },
+    /// EstimatedApr shows the yield of the liquid token over the last `window_seconds`, annualized
+    EstimatedApr { window_seconds: u64 },
+    /// ProposalTally shows the votes of the liquid token holders on a registered proposal
+    ProposalTally { proposal_id: u64 },
+    /// VoteOf shows the vote of this address on a proposal and its weight
+    VoteOf { proposal_id: u64, address: String },
+    /// RewardsOf shows the native tokens of rewards this address can claim in index reward mode
+    RewardsOf { address: String },
+    /// VotingPowerOf shows the liquid tokens locked by this address to vote on validator proposals
+    VotingPowerOf { address: String },
+    /// ValidatorProposal shows a validator proposal with its status
+    ValidatorProposal { proposal_id: u64 },
+    /// ValidatorProposals shows the validator proposals by id
+    ValidatorProposals { start_after: Option<u64>, limit: Option<u32> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -55,9 +230,48 @@ pub struct ConfigResponse {
     pub bond_denom: String,
     /// Liquid token address
     pub liquid_token_addr: String,
-    /// All tokens are bonded to this validator
-    /// FIXME: address validation doesn't work for validator addresses
-    pub validator: String,
+    /// Token-factory denom of the liquid token, if used instead of the cw20 liquid token
+    pub liquid_denom: Option<String>,
+    /// All tokens are bonded to these validators, split according to their weights
+    pub validators: Vec<ValidatorInfo>,
+    /// Unbonding period of the chain in seconds
+    pub unbonding_period: u64,
+    /// Drift from the target weights that triggers an automatic rebalance
+    pub rebalance_threshold: Decimal,
+    /// Minimum number of seconds between two undelegations
+    pub epoch_period: u64,
+    /// Share of the staking rewards taken as protocol fee, in basis points
+    pub protocol_fee: u64,
+    /// Protocol fees are sent to this address
+    pub treasury: String,
+    /// Share of the harvested staking rewards paid to the caller of Harvest, in basis points
+    pub harvest_bounty: u64,
+    /// Minimum number of blocks between two harvests
+    pub harvest_interval: u64,
+    /// Native tokens liquid token holders can withdraw are capped to this amount
+    pub max_tvl: Option<Uint128>,
+    /// Minimum amount of native tokens per stake
+    pub min_stake: Uint128,
+    /// Minimum amount of native tokens per unstake request
+    pub min_unstake: Uint128,
+    /// Native tokens a single address can hold in liquid tokens are capped to this amount
+    pub max_stake_per_address: Option<Uint128>,
+    /// Swap contract used for instant unstakes
+    pub swap_contract: Option<String>,
+    /// Guardian can pause operations during an incident
+    pub guardian: Option<String>,
+    /// Operations currently paused
+    pub paused: PauseInfo,
+    /// Liquid token holders can change the validator set through proposals when set
+    pub validator_gov: Option<ValidatorGovConfig>,
+    /// Unit of the unstake requests waiting in the queue
+    pub queue_mode: QueueMode,
+    /// How the staking rewards reach the liquid token holders
+    pub reward_mode: RewardMode,
+    /// Proposed new owner, waiting to accept the ownership
+    pub pending_owner: Option<String>,
+    /// The ownership proposal can no longer be accepted after this time
+    pub pending_owner_expires: Option<Timestamp>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -68,18 +282,135 @@ pub struct StatusResponse {
     pub native: Coin,
     /// unstakings is how many total native tokens in unstaking queue
     pub unstakings: Uint128,
+    /// unstaking_liquid is how many liquid tokens the requests of the open epoch hold in liquid queue mode
+    pub unstaking_liquid: Uint128,
+    /// rewards is how many native tokens of rewards were not claimed yet in index reward mode
+    pub rewards: Uint128,
+    /// native tokens of rewards per liquid token since instantiation in index reward mode
+    pub reward_index: Decimal,
     /// claims is how many tokens need to be reserved paying back those who unbonded
     pub claims: Uint128,
-    /// bonded is how many native tokens exist bonded to the validator
+    /// bonded is how many native tokens exist bonded to the validators
     pub bonded: Uint128,
     /// available native token balance of this contract
     pub balance: Uint128,
     /// ratio of native / issued (or how many native tokens that one derivative token is nominally worth)
     pub ratio: Decimal,
+    /// share of the staking rewards taken as protocol fee, in basis points
+    pub protocol_fee: u64,
+    /// fees is how many native tokens from staking rewards were sent to the treasury
+    pub fees: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct UnstakingQueueResponse {
     pub state: LinkedList,
     pub queue: Vec<NodeWithId>,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnstakingRequestsResponse {
+    pub requests: Vec<NodeWithId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingUnbonding {
+    /// id of the request in the unstaking queue
+    pub id: u64,
+    /// amount of native tokens still to be paid out, at the current ratio in liquid queue mode
+    pub amount: Uint128,
+    /// epoch whose undelegation funds this request
+    pub epoch: u64,
+    /// estimated time the request becomes claimable
+    pub release_time: Timestamp,
+    /// matured is true once the undelegation funding this request completed
+    pub matured: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingUnbondingsResponse {
+    pub unbondings: Vec<PendingUnbonding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RatioHistoryResponse {
+    pub snapshots: Vec<RatioSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EstimatedAprResponse {
+    /// annualized growth of the exchange rate, zero when it didn't grow or there is not enough history
+    pub apr: Decimal,
+    /// oldest snapshot within the window
+    pub start: Option<RatioSnapshot>,
+    /// latest snapshot
+    pub end: Option<RatioSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateStakeResponse {
+    /// liquid tokens that would be minted
+    pub minted: Uint128,
+    /// ratio of native / issued after compounding the pending staking rewards
+    pub ratio: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateUnstakeResponse {
+    /// native tokens that would be queued
+    pub native_amount: Uint128,
+    /// position of the request in the unstaking queue, starting at 1
+    pub queue_position: u64,
+    /// native tokens queued ahead of the request
+    pub queued_ahead: Uint128,
+    /// epoch whose undelegation would fund the request
+    pub epoch: u64,
+    /// estimated time the request becomes claimable
+    pub release_time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalTallyResponse {
+    pub proposal_id: u64,
+    /// end of the voting period, none if the proposal is not registered
+    pub voting_end: Option<Timestamp>,
+    pub tally: Tally,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteResponse {
+    /// vote of the address, if it voted
+    pub option: Option<VoteOption>,
+    /// liquid tokens locked by the address at the start height of the proposal
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    /// liquid tokens locked by the address
+    pub power: Uint128,
+    /// liquid tokens locked by all the holders
+    pub total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorProposalResponse {
+    pub id: u64,
+    pub proposal: ValidatorProposal,
+    pub status: ProposalStatus,
+    /// time a passed proposal can be executed
+    pub executable_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorProposalsResponse {
+    pub proposals: Vec<ValidatorProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardsResponse {
+    /// native tokens of rewards the address can claim now
+    pub rewards: Uint128,
+    /// global reward index
+    pub index: Decimal,
+}
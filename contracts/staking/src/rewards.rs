@@ -0,0 +1,43 @@
+use cosmwasm_std::{Addr, Decimal, StdResult, Storage, Uint128};
+
+use crate::state::{HolderRewards, Supply, HOLDER_REWARDS, REWARD_INDEX, UNALLOCATED_REWARDS, VOTING_POWER};
+
+// add_rewards makes up for the slashing losses under the peg out of `amount` native tokens, then spreads the
+// rest, with the unallocated ones, over the `locked` liquid tokens through the global reward index. They stay
+// unallocated until the next rewards while no liquid tokens are locked
+pub fn add_rewards(storage: &mut dyn Storage, supply: &mut Supply, amount: Uint128, locked: Uint128) -> StdResult<Decimal> {
+    let restored = amount.min(supply.peg_shortfall);
+    supply.peg_shortfall -= restored;
+    let amount = amount - restored;
+    supply.rewards += amount;
+    let amount = amount + UNALLOCATED_REWARDS.may_load(storage)?.unwrap_or_default();
+    let mut index = REWARD_INDEX.may_load(storage)?.unwrap_or_default();
+    if locked.is_zero() {
+        UNALLOCATED_REWARDS.save(storage, &amount)?;
+    } else {
+        index += Decimal::from_ratio(amount, locked);
+        REWARD_INDEX.save(storage, &index)?;
+        UNALLOCATED_REWARDS.save(storage, &Uint128::zero())?;
+    }
+    Ok(index)
+}
+
+// pending_rewards returns the rewards of `holder` as if it was checkpointed now
+pub fn pending_rewards(storage: &dyn Storage, holder: &Addr) -> StdResult<Uint128> {
+    let index = REWARD_INDEX.may_load(storage)?.unwrap_or_default();
+    let rewards = HOLDER_REWARDS.may_load(storage, holder)?.unwrap_or_default();
+    let locked = VOTING_POWER.may_load(storage, holder)?.unwrap_or_default();
+    Ok(rewards.pending + locked * (index - rewards.index))
+}
+
+// checkpoint accrues the rewards of `holder` since its last checkpoint. Only the liquid tokens locked in the
+// contract earn rewards, it must run before they change
+pub fn checkpoint(storage: &mut dyn Storage, holder: &Addr) -> StdResult<HolderRewards> {
+    let index = REWARD_INDEX.may_load(storage)?.unwrap_or_default();
+    let mut rewards = HOLDER_REWARDS.may_load(storage, holder)?.unwrap_or_default();
+    let locked = VOTING_POWER.may_load(storage, holder)?.unwrap_or_default();
+    rewards.pending += locked * (index - rewards.index);
+    rewards.index = index;
+    HOLDER_REWARDS.save(storage, holder, &rewards)?;
+    Ok(rewards)
+}
@@ -0,0 +1,55 @@
+use cosmwasm_std::{Order, StdError, StdResult, Storage, Timestamp, Uint128};
+
+use crate::state::{Supply, UNDELEGATIONS};
+
+// SLASHING_DUST is the shortfall the reconciliation ignores, delegation shares round by a token or so
+pub const SLASHING_DUST: Uint128 = Uint128::new(100);
+
+// in_flight_undelegations returns the native tokens undelegated by the contract that may not have
+// been returned by the staking module yet. The staking module pays an undelegation at the end of the
+// first block past its completion, it is counted until a later block.
+pub fn in_flight_undelegations(storage: &mut dyn Storage, now: Timestamp, height: u64) -> StdResult<Uint128> {
+    let mut in_flight = Uint128::zero();
+    let mut matured = vec![];
+    // undelegations complete in epoch order, stop at the first one whose tokens surely landed
+    for item in UNDELEGATIONS.range(storage, None, None, Order::Descending) {
+        let (id, undelegation) = item?;
+        if undelegation.completion <= now {
+            match undelegation.matured_at {
+                Some(matured_at) if matured_at < height => break,
+                Some(_) => {}
+                None => matured.push((id, undelegation.clone())),
+            }
+        }
+        in_flight += undelegation.amount;
+    }
+    for (id, mut undelegation) in matured {
+        undelegation.matured_at = Some(height);
+        UNDELEGATIONS.save(storage, id, &undelegation)?;
+    }
+    Ok(in_flight)
+}
+
+// socialize_loss spreads `loss` pro rata over the liquid token holders (lowering the exchange rate, or
+// adding to the peg shortfall when `keep_peg`) and the requests in the unstaking queue. It returns the part
+// taken from the unstaking queue.
+pub fn socialize_loss(supply: &mut Supply, loss: Uint128, keep_peg: bool) -> StdResult<Uint128> {
+    let native = supply.native.saturating_sub(supply.peg_shortfall);
+    let total = native + supply.unstakings;
+    let loss = loss.min(total);
+    if loss.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    // the requests share `unstakings` by their units, lowering it cuts all of them. Liquid requests of the
+    // open epoch are not in `unstakings` yet, they bear the loss through the exchange rate
+    let unstaking_loss = loss.multiply_ratio(supply.unstakings, total);
+    supply.unstakings = supply.unstakings.checked_sub(unstaking_loss).map_err(StdError::overflow)?;
+    // the rest, including rounding, lowers the exchange rate
+    let native_loss = (loss - unstaking_loss).min(native);
+    if keep_peg {
+        supply.peg_shortfall += native_loss;
+    } else {
+        supply.native -= native_loss;
+    }
+    Ok(unstaking_loss)
+}
@@ -1,8 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128, VoteOption};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+
+use crate::governance::Tally;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigInfo {
@@ -12,9 +14,175 @@ pub struct ConfigInfo {
     pub bond_denom: String,
     /// Liquid token address
     pub liquid_token_addr: Addr,
-    /// All tokens are bonded to this validator
-    /// FIXME: address validation doesn't work for validator addresses
-    pub validator: String,
+    /// Token-factory denom of the liquid token, replaces the cw20 liquid token when set
+    pub liquid_denom: Option<String>,
+    /// All tokens are bonded to these validators, split according to their weights
+    pub validators: Vec<ValidatorInfo>,
+    /// Unbonding period of the chain in seconds
+    pub unbonding_period: u64,
+    /// Stake is redelegated back toward the target weights once a validator drifts
+    /// from its target by more than this share of the total bonded (zero disables it)
+    pub rebalance_threshold: Decimal,
+    /// Minimum number of seconds between two undelegations, unstake requests are gathered
+    /// into epochs of this length and undelegated together
+    pub epoch_period: u64,
+    /// Share of the staking rewards taken as protocol fee, in basis points
+    pub protocol_fee: u64,
+    /// Protocol fees are sent to this address
+    pub treasury: Addr,
+    /// Share of the harvested staking rewards paid to the caller of Harvest, in basis points
+    pub harvest_bounty: u64,
+    /// Minimum number of blocks between two harvests
+    pub harvest_interval: u64,
+    /// Native tokens liquid token holders can withdraw are capped to this amount
+    pub max_tvl: Option<Uint128>,
+    /// Minimum amount of native tokens per stake
+    pub min_stake: Uint128,
+    /// Minimum amount of native tokens per unstake request
+    pub min_unstake: Uint128,
+    /// Native tokens a single address can hold in liquid tokens are capped to this amount
+    pub max_stake_per_address: Option<Uint128>,
+    /// Swap contract used for instant unstakes
+    pub swap_contract: Option<Addr>,
+    /// Guardian can pause operations during an incident, only the owner can unpause them
+    pub guardian: Option<Addr>,
+    /// Operations currently paused
+    pub paused: PauseInfo,
+    /// Liquid token holders can change the validator set through proposals when set
+    pub validator_gov: Option<ValidatorGovConfig>,
+    /// Unit of the unstake requests waiting in the queue, set at instantiation
+    pub queue_mode: QueueMode,
+    /// How the staking rewards reach the liquid token holders, set at instantiation
+    pub reward_mode: RewardMode,
+}
+
+/// RewardMode is how the staking rewards reach the liquid token holders
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardMode {
+    /// Rewards are staked and raise the exchange rate of the liquid token
+    Compound,
+    /// The liquid token stays pegged 1:1 and every reward is added to a global reward index once slashing
+    /// losses are made up for. Only the liquid tokens locked as voting power earn rewards, their holders
+    /// claim the native tokens they accrued with ClaimRewards
+    Index,
+}
+
+/// HolderRewards is the reward checkpoint of a liquid token holder in index reward mode
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct HolderRewards {
+    /// global reward index at the last checkpoint, the locked liquid tokens accrue rewards since then
+    pub index: Decimal,
+    /// native tokens accrued and not claimed yet
+    pub pending: Uint128,
+}
+
+/// QueueMode is the unit the unstake requests are kept in while waiting in the queue
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueMode {
+    /// Requests are converted to native tokens when queued, they stop earning rewards and
+    /// only bear the slashing of their own tokens
+    Native,
+    /// Requests keep the liquid tokens burned and are converted to native tokens when their epoch is
+    /// undelegated, they keep earning rewards and bearing slashing like the liquid tokens until then
+    Liquid,
+}
+
+/// ValidatorGovConfig sets how liquid token holders vote on changes to the validator set
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorGovConfig {
+    /// Number of seconds a validator proposal is open for voting
+    pub voting_period: u64,
+    /// Number of seconds between the end of the voting period and the execution of a passed proposal
+    pub timelock: u64,
+    /// Share of the liquid tokens issued that must vote for a proposal to be valid
+    pub quorum: Decimal,
+    /// Share of the yes and no votes that must be yes for a proposal to pass
+    pub threshold: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseInfo {
+    /// Stake is paused, cancelling unstake requests too
+    pub stake: bool,
+    /// Unstake (liquid tokens received from the cw20 contract) is paused, cancelling unstake requests too
+    pub unstake: bool,
+    /// Claim is paused
+    pub claim: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorInfo {
+    /// Validator operator address
+    pub address: String,
+    /// Target weight of this validator, relative to the sum of all weights in the set
+    pub weight: u64,
+}
+
+/// ValidatorChange is one change to the validator set made by a validator proposal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorChange {
+    /// Add a validator to the set
+    Add { address: String, weight: u64 },
+    /// Remove a validator from the set, its stake follows the remaining weights
+    Remove { address: String },
+    /// Change the target weight of a validator of the set
+    SetWeight { address: String, weight: u64 },
+}
+
+/// ValidatorProposal is a change to the validator set voted by the liquid token holders
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorProposal {
+    pub proposer: Addr,
+    pub changes: Vec<ValidatorChange>,
+    /// voting power is the liquid tokens locked at the start of this block
+    pub start_height: u64,
+    /// votes are accepted until this time
+    pub voting_end: Timestamp,
+    /// liquid tokens issued when the proposal was created, locked or not, the quorum is a share of them
+    pub total_power: Uint128,
+    /// quorum, threshold and timelock in force when the proposal was created
+    pub rules: ValidatorGovConfig,
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub abstain: Uint128,
+    /// executed is true once the changes were applied to the validator set
+    pub executed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Ballot {
+    Yes,
+    No,
+    Abstain,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorVote {
+    pub ballot: Ballot,
+    /// voting power of the voter at the start height of the proposal
+    pub weight: Uint128,
+}
+
+/// GovProposal is a governance proposal of the chain registered by the owner for liquid token holders to vote on
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GovProposal {
+    /// end of the voting period of the proposal on chain
+    pub voting_end: Timestamp,
+    /// height the proposal was registered at, votes weigh the voting power locked before this block
+    pub start_height: u64,
+    /// running tally of the votes of the liquid token holders
+    pub tally: Tally,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GovVote {
+    pub option: VoteOption,
+    /// liquid tokens locked by the voter at the start height of the proposal
+    pub weight: Uint128,
 }
 
 /// Supply is dynamic and tracks the current supply of staked and ERC20 tokens.
@@ -24,11 +192,154 @@ pub struct Supply {
     pub native: Uint128,
     // unstakings is how many total native tokens in unstaking queue
     pub unstakings: Uint128,
+    /// unstaking_units is how many units the requests of the unstaking queue hold, they share `unstakings`
+    /// so a slash lowers every request at once. In liquid queue mode requests get units once undelegated
+    pub unstaking_units: Uint128,
     /// claims is how many tokens need to be reserved paying back those who unbonded
     pub claims: Uint128,
+    /// fees is how many native tokens from staking rewards were sent to the treasury
+    pub fees: Uint128,
+    /// issued is how many liquid tokens were minted, only tracked for the token-factory denom
+    pub issued: Uint128,
+    /// unstaking_liquid is how many liquid tokens were burned by the requests of the open epoch
+    /// in liquid queue mode, they still share `native` with the issued liquid tokens
+    pub unstaking_liquid: Uint128,
+    /// rewards is how many native tokens were added to the reward index and not claimed yet,
+    /// including the unallocated ones
+    pub rewards: Uint128,
+    /// peg_shortfall is how many of the `native` tokens were lost to slashing in index reward mode,
+    /// the liquid token keeps its peg and the next rewards make up for them
+    pub peg_shortfall: Uint128,
+}
+
+/// OwnershipProposal is a pending transfer of the owner role, it takes effect once accepted by the new owner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    /// Proposed new owner
+    pub owner: Addr,
+    /// The proposal can no longer be accepted after this time
+    pub expires: Option<Timestamp>,
+}
+
+/// Epoch gathers the unstake requests that are undelegated together
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Epoch {
+    /// id is stored with every unstake request queued during this epoch
+    pub id: u64,
+    /// start is when this epoch was opened
+    pub start: Timestamp,
+    /// pending is how many native tokens (liquid tokens in liquid queue mode) were requested for
+    /// unstaking during this epoch
+    pub pending: Uint128,
+}
+
+/// Undelegation is sent once per epoch to fund the unstake requests queued during it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Undelegation {
+    /// amount of native tokens undelegated
+    pub amount: Uint128,
+    /// time the undelegation was sent
+    pub time: Timestamp,
+    /// time the undelegated tokens are expected back, after the unbonding period
+    pub completion: Timestamp,
+    /// height the undelegation was first seen completed, its tokens are in the balance from the next block on
+    pub matured_at: Option<u64>,
+    /// liquid tokens of the requests of the epoch in liquid queue mode, converted to `units` when undelegated
+    pub liquid: Uint128,
+    /// units of the unstaking queue the requests of the epoch hold
+    pub units: Uint128,
+}
+
+/// RatioSnapshot records the exchange rate after the staking rewards were compounded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RatioSnapshot {
+    /// block height of the snapshot
+    pub height: u64,
+    /// block time of the snapshot
+    pub time: Timestamp,
+    /// native tokens liquid token holders could withdraw
+    pub native: Uint128,
+    /// liquid tokens issued, including the ones burned by queued requests in liquid queue mode
+    pub issued: Uint128,
+    /// ratio of native / issued
+    pub ratio: Decimal,
+}
+
+/// PendingProcess carries the state of perform_check until the staking rewards are claimed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingProcess {
+    /// native token balance of the contract before claiming the rewards
+    pub balance_before: Uint128,
+    /// liquid tokens to mint once the rewards are compounded
+    pub mint: Option<PendingMint>,
+    /// caller of Harvest, paid the harvest bounty out of the rewards
+    pub harvester: Option<Addr>,
+    /// cw20 liquid tokens whose burn only runs after a process that finished right away,
+    /// still counted by the token supply
+    pub burning: Uint128,
+}
+
+/// PendingInstantUnstake carries an instant unstake until the swap contract paid the native tokens
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingInstantUnstake {
+    /// receiver of the native tokens
+    pub recipient: Addr,
+    /// native token balance of the contract before the swap
+    pub balance_before: Uint128,
+    /// minimum amount of native tokens the swap must pay
+    pub min_native: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingMint {
+    /// receiver of the liquid tokens
+    pub receiver: Addr,
+    /// native tokens staked
+    pub native_amount: Uint128,
 }
 
 pub const CONFIG: Item<ConfigInfo> = Item::new("config");
 pub const TOTAL_SUPPLY: Item<Supply> = Item::new("total_supply");
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
+pub const PENDING_PROCESS: Item<PendingProcess> = Item::new("pending_process");
+pub const PENDING_INSTANT_UNSTAKE: Item<PendingInstantUnstake> = Item::new("pending_instant_unstake");
+/// block height of the last harvest
+pub const LAST_HARVEST: Item<u64> = Item::new("last_harvest");
 pub const CLAIMABLE: Map<&Addr, Uint128> = Map::new("claimable");
-pub const UNDER_UNSTAKING: Map<&Addr, Uint128> = Map::new("under_unstaking");
\ No newline at end of file
+pub const UNDER_UNSTAKING: Map<&Addr, Uint128> = Map::new("under_unstaking");
+/// Completion times of the redelegations in flight, by source and destination validator
+pub const REDELEGATIONS: Map<(&str, &str), Vec<Timestamp>> = Map::new("redelegations");
+pub const CURRENT_EPOCH: Item<Epoch> = Item::new("current_epoch");
+/// Undelegations sent so far, by epoch id
+pub const UNDELEGATIONS: Map<u64, Undelegation> = Map::new("undelegations");
+/// exchange rate snapshots by block height
+pub const RATIO_SNAPSHOTS: Map<u64, RatioSnapshot> = Map::new("ratio_snapshots");
+/// native tokens of rewards per locked liquid token since instantiation, in index reward mode
+pub const REWARD_INDEX: Item<Decimal> = Item::new("reward_index");
+pub const HOLDER_REWARDS: Map<&Addr, HolderRewards> = Map::new("holder_rewards");
+/// native tokens of rewards earned while no liquid tokens were locked, added to the index with the next rewards
+pub const UNALLOCATED_REWARDS: Item<Uint128> = Item::new("unallocated_rewards");
+/// liquid tokens locked by each holder to vote on validator proposals
+pub const VOTING_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_VOTING_POWER: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_voting_power",
+    "total_voting_power__checkpoints",
+    "total_voting_power__changelog",
+    Strategy::EveryBlock,
+);
+/// id of the last validator proposal
+pub const VALIDATOR_PROPOSAL_COUNT: Item<u64> = Item::new("validator_proposal_count");
+pub const VALIDATOR_PROPOSALS: Map<u64, ValidatorProposal> = Map::new("validator_proposals");
+/// votes on the validator proposals, by proposal id and voter
+pub const VALIDATOR_VOTES: Map<(u64, &Addr), ValidatorVote> = Map::new("validator_votes");
+/// governance proposals of the chain open to the votes of the liquid token holders, by proposal id
+pub const GOV_PROPOSALS: Map<u64, GovProposal> = Map::new("gov_proposals");
+/// ids of the registered governance proposals that may still be open for voting
+pub const OPEN_GOV_PROPOSALS: Item<Vec<u64>> = Item::new("open_gov_proposals");
+/// votes of the liquid token holders on governance proposals of the chain, by proposal id and voter
+pub const VOTES: Map<(u64, &Addr), GovVote> = Map::new("votes");
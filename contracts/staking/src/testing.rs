@@ -0,0 +1,1240 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::testing::{
+    mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
+    Decimal, DistributionMsg, Env, FullDelegation, GovMsg, OwnedDeps, Reply, ReplyOn, StakingMsg,
+    SubMsg, SubMsgResponse, SubMsgResult, SystemResult, Uint128, Validator, VoteOption,
+    WeightedVoteOption,
+};
+use cosmwasm_storage::{bucket, singleton};
+use cw2::set_contract_version;
+use cw20::{BalanceResponse, Cw20ReceiveMsg, TokenInfoResponse};
+use cw_storage_plus::Item;
+use cw_utils::PaymentError;
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::{
+    BindingMsg, BindingQuery, FullDenomResponse, RewardsRecord, RewardsRecordsResponse,
+};
+use crate::contract::{execute, instantiate, migrate, query, reply};
+use crate::governance::ProposalStatus;
+use crate::linked_list::{LINKED_LIST, NODES};
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, LiquidTokenInfo, MigrateMsg, ProposalTallyResponse, QueryMsg,
+    RatioHistoryResponse, ReceiveMsg, RewardsResponse, SimulateStakeResponse, StatusResponse,
+    ValidatorProposalResponse,
+};
+use crate::state::{
+    Ballot, QueueMode, RewardMode, ValidatorChange, ValidatorGovConfig, ValidatorInfo, CONFIG,
+    TOTAL_SUPPLY, UNDELEGATIONS, UNDER_UNSTAKING,
+};
+use crate::ContractError;
+
+const DENOM: &str = "uconst";
+const VALIDATOR: &str = "validator";
+const SUBDENOM: &str = "stkconst";
+const LIQUID_DENOM: &str = "factory/cosmos2contract/stkconst";
+const UNBONDING_PERIOD: u64 = 21 * 24 * 60 * 60;
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier<BindingQuery>, BindingQuery>;
+
+fn mock_deps() -> MockDeps {
+    mock_deps_with_records(vec![])
+}
+
+// mock_deps_with_records answers the token-factory queries and returns `records` as the Archway
+// rewards records of the contract, the staking module knows a single validator
+fn mock_deps_with_records(records: Vec<RewardsRecord>) -> MockDeps {
+    let mut querier =
+        MockQuerier::<BindingQuery>::new(&[]).with_custom_handler(move |query| match query {
+            BindingQuery::FullDenom {
+                creator_addr,
+                subdenom,
+            } => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&FullDenomResponse {
+                    denom: format!("factory/{}/{}", creator_addr, subdenom),
+                })
+                .unwrap(),
+            )),
+            BindingQuery::RewardsRecords {
+                rewards_address, ..
+            } => {
+                assert_eq!(rewards_address, MOCK_CONTRACT_ADDR);
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&RewardsRecordsResponse {
+                        records: records.clone(),
+                    })
+                    .unwrap(),
+                ))
+            }
+        });
+    querier.update_staking(DENOM, &[mock_validator()], &[]);
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+fn rewards_record(id: u64, rewards: Vec<Coin>) -> RewardsRecord {
+    RewardsRecord {
+        id,
+        rewards_address: MOCK_CONTRACT_ADDR.to_string(),
+        rewards,
+        calculated_height: 12345,
+        calculated_time: "2022-10-01T00:00:00Z".to_string(),
+    }
+}
+
+fn mock_validator() -> Validator {
+    Validator {
+        address: VALIDATOR.to_string(),
+        commission: Decimal::percent(5),
+        max_commission: Decimal::percent(20),
+        max_change_rate: Decimal::percent(1),
+    }
+}
+
+// set_delegation records `amount` native tokens of the contract as delegated to the validator
+fn set_delegation(deps: &mut MockDeps, amount: u128) {
+    let delegation = FullDelegation {
+        delegator: Addr::unchecked(MOCK_CONTRACT_ADDR),
+        validator: VALIDATOR.to_string(),
+        amount: coin(amount, DENOM),
+        can_redelegate: coin(amount, DENOM),
+        accumulated_rewards: vec![],
+    };
+    deps.querier
+        .update_staking(DENOM, &[mock_validator()], &[delegation]);
+}
+
+fn instantiate_msg(liquid_token: LiquidTokenInfo) -> InstantiateMsg {
+    InstantiateMsg {
+        liquid_token,
+        validators: vec![ValidatorInfo {
+            address: VALIDATOR.to_string(),
+            weight: 1,
+        }],
+        unbonding_period: UNBONDING_PERIOD,
+        rebalance_threshold: Decimal::zero(),
+        epoch_period: UNBONDING_PERIOD / 6,
+        protocol_fee: 0,
+        treasury: "treasury".to_string(),
+        harvest_bounty: 0,
+        harvest_interval: 0,
+        max_tvl: None,
+        min_stake: Uint128::zero(),
+        min_unstake: Uint128::zero(),
+        max_stake_per_address: None,
+        swap_contract: None,
+        guardian: None,
+        validator_gov: None,
+        queue_mode: QueueMode::Native,
+        reward_mode: RewardMode::Compound,
+    }
+}
+
+fn instantiate_token_factory(deps: &mut MockDeps) {
+    let msg = instantiate_msg(LiquidTokenInfo::TokenFactory {
+        subdenom: SUBDENOM.to_string(),
+    });
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+}
+
+// stake sends `amount` native tokens from `staker`, the contract balance holds them once the message runs
+fn stake(deps: &mut MockDeps, staker: &str, amount: u128) {
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(amount, DENOM));
+    let info = mock_info(staker, &coins(amount, DENOM));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Stake { recipient: None },
+    )
+    .unwrap();
+    // the process delegated the staked tokens
+    deps.querier.update_balance(MOCK_CONTRACT_ADDR, vec![]);
+    set_delegation(deps, amount);
+}
+
+fn unstake_denom(
+    deps: &mut MockDeps,
+    funds: &[Coin],
+) -> Result<cosmwasm_std::Response<BindingMsg>, ContractError> {
+    let info = mock_info("staker", funds);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Unstake { recipient: None },
+    )
+}
+
+#[test]
+fn instantiate_creates_liquid_denom() {
+    let mut deps = mock_deps();
+    let msg = instantiate_msg(LiquidTokenInfo::TokenFactory {
+        subdenom: SUBDENOM.to_string(),
+    });
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BindingMsg::CreateDenom {
+            subdenom: SUBDENOM.to_string()
+        })]
+    );
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.liquid_denom, Some(LIQUID_DENOM.to_string()));
+    assert_eq!(config.bond_denom, DENOM);
+}
+
+#[test]
+fn reply_stores_cw20_token_address() {
+    let mut deps = mock_deps();
+    let msg = instantiate_msg(LiquidTokenInfo::Cw20 {
+        code_id: 1,
+        name: "Staked Const".to_string(),
+        symbol: "STKCONST".to_string(),
+        decimals: 6,
+    });
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Success);
+    assert_eq!(CONFIG.load(&deps.storage).unwrap().liquid_denom, None);
+
+    // MsgInstantiateContractResponse with the address of the token as field 1
+    let address = "liquidtoken";
+    let mut data = vec![0x0a, address.len() as u8];
+    data.extend_from_slice(address.as_bytes());
+    let token_reply = Reply {
+        id: res.messages[0].id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(Binary::from(data)),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), token_reply).unwrap();
+    assert_eq!(
+        CONFIG.load(&deps.storage).unwrap().liquid_token_addr,
+        Addr::unchecked(address)
+    );
+
+    // an unknown reply id is rejected
+    let unknown = Reply {
+        id: 99,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let err = reply(deps.as_mut(), mock_env(), unknown).unwrap_err();
+    assert!(matches!(err, ContractError::UnknownReplyId { id: 99 }));
+}
+
+#[test]
+fn stake_mints_liquid_denom() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1000, DENOM));
+    let info = mock_info("staker", &coins(1000, DENOM));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Stake { recipient: None },
+    )
+    .unwrap();
+
+    let delegate = StakingMsg::Delegate {
+        validator: VALIDATOR.to_string(),
+        amount: coin(1000, DENOM),
+    };
+    let mint = BindingMsg::MintTokens {
+        denom: LIQUID_DENOM.to_string(),
+        amount: Uint128::new(1000),
+        mint_to_address: "staker".to_string(),
+    };
+    assert_eq!(res.messages, vec![SubMsg::new(delegate), SubMsg::new(mint)]);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(1000));
+    assert_eq!(supply.issued, Uint128::new(1000));
+}
+
+#[test]
+fn unstake_burns_liquid_denom() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+
+    let res = unstake_denom(&mut deps, &coins(400, LIQUID_DENOM)).unwrap();
+    let burn = BindingMsg::BurnTokens {
+        denom: LIQUID_DENOM.to_string(),
+        amount: Uint128::new(400),
+        burn_from_address: MOCK_CONTRACT_ADDR.to_string(),
+    };
+    // the tokens are processed in the reply to the reward withdrawal
+    assert_eq!(res.messages[0], SubMsg::new(burn));
+    assert_eq!(
+        res.messages[1].msg,
+        DistributionMsg::WithdrawDelegatorReward {
+            validator: VALIDATOR.to_string()
+        }
+        .into()
+    );
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.issued, Uint128::new(600));
+    assert_eq!(supply.native, Uint128::new(600));
+    assert_eq!(supply.unstakings, Uint128::new(400));
+
+    let query_msg = QueryMsg::UnderUnstakingOf {
+        address: "staker".to_string(),
+    };
+    let under_unstaking: BalanceResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    assert_eq!(under_unstaking.balance, Uint128::new(400));
+}
+
+#[test]
+fn unstake_rejects_other_funds() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+
+    let err = unstake_denom(&mut deps, &[]).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Payment(PaymentError::NoFunds {})
+    ));
+    let err = unstake_denom(&mut deps, &coins(400, DENOM)).unwrap_err();
+    assert!(
+        matches!(err, ContractError::Payment(PaymentError::MissingDenom(denom)) if denom == LIQUID_DENOM)
+    );
+    let err = unstake_denom(&mut deps, &[coin(400, LIQUID_DENOM), coin(1, DENOM)]).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Payment(PaymentError::MultipleDenoms {})
+    ));
+
+    // nothing was queued
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.issued, Uint128::new(1000));
+    assert!(supply.unstakings.is_zero());
+}
+
+#[test]
+fn unstake_denom_needs_token_factory() {
+    let mut deps = mock_deps();
+    let msg = instantiate_msg(LiquidTokenInfo::Cw20 {
+        code_id: 1,
+        name: "Staked Const".to_string(),
+        symbol: "STKCONST".to_string(),
+        decimals: 6,
+    });
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let err = unstake_denom(&mut deps, &coins(400, LIQUID_DENOM)).unwrap_err();
+    assert!(matches!(err, ContractError::NoLiquidDenom {}));
+}
+
+#[test]
+fn status_reports_liquid_denom_supply() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+    unstake_denom(&mut deps, &coins(250, LIQUID_DENOM)).unwrap();
+
+    let status: StatusResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::StatusInfo {}).unwrap()).unwrap();
+    assert_eq!(status.issued, Uint128::new(750));
+    assert_eq!(status.native, coin(750, DENOM));
+    assert_eq!(status.unstakings, Uint128::new(250));
+    assert_eq!(status.bonded, Uint128::new(1000));
+    assert_eq!(status.ratio, Decimal::one());
+}
+
+#[test]
+fn withdraw_archway_rewards_adds_to_native() {
+    let records = vec![
+        rewards_record(1, coins(30, DENOM)),
+        rewards_record(2, vec![coin(20, DENOM), coin(500, "uother")]),
+    ];
+    let mut deps = mock_deps_with_records(records);
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+
+    let info = mock_info("anyone", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawArchwayRewards {},
+    )
+    .unwrap();
+    let withdraw = BindingMsg::WithdrawRewards {
+        records_limit: None,
+        record_ids: vec![1, 2],
+    };
+    assert_eq!(res.messages, vec![SubMsg::new(withdraw)]);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(1050));
+    assert_eq!(supply.issued, Uint128::new(1000));
+
+    // the raised exchange rate is recorded
+    let query_msg = QueryMsg::RatioHistory {
+        start_after: None,
+        limit: None,
+    };
+    let history: RatioHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    assert_eq!(history.snapshots.len(), 1);
+    assert_eq!(
+        history.snapshots[0].ratio,
+        Decimal::from_ratio(1050u128, 1000u128)
+    );
+}
+
+#[test]
+fn withdraw_archway_rewards_without_rewards() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+
+    // no records to withdraw
+    let info = mock_info("anyone", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawArchwayRewards {},
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::NoArchwayRewards {}));
+
+    // records paying other denoms are withdrawn without changing the supply
+    let mut deps = mock_deps_with_records(vec![rewards_record(7, coins(500, "uother"))]);
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+    let info = mock_info("anyone", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawArchwayRewards {},
+    )
+    .unwrap();
+    let withdraw = BindingMsg::WithdrawRewards {
+        records_limit: None,
+        record_ids: vec![7],
+    };
+    assert_eq!(res.messages, vec![SubMsg::new(withdraw)]);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(1000));
+}
+
+fn vote(
+    deps: &mut MockDeps,
+    env: Env,
+    voter: &str,
+    option: VoteOption,
+) -> Result<cosmwasm_std::Response<BindingMsg>, ContractError> {
+    let msg = ExecuteMsg::Vote {
+        proposal_id: 1,
+        option,
+    };
+    execute(deps.as_mut(), env, mock_info(voter, &[]), msg)
+}
+
+fn query_tally(deps: &MockDeps) -> ProposalTallyResponse {
+    let query_msg = QueryMsg::ProposalTally { proposal_id: 1 };
+    from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap()
+}
+
+#[test]
+fn votes_keep_running_tally() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    lock(&mut deps, "alice", 600);
+    lock(&mut deps, "bob", 400);
+    // the voting power is taken at the start of the block the proposal is registered in
+    let mut env = mock_env();
+    env.block.height += 1;
+    let voting_end = env.block.time.plus_seconds(3600);
+
+    // only registered proposals can be voted on, registered by the owner
+    let err = vote(&mut deps, env.clone(), "alice", VoteOption::Yes).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::GovProposalNotFound { proposal_id: 1 }
+    ));
+    let register = ExecuteMsg::RegisterProposal {
+        proposal_id: 1,
+        voting_end,
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        register.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        register,
+    )
+    .unwrap();
+
+    vote(&mut deps, env.clone(), "alice", VoteOption::Yes).unwrap();
+    vote(&mut deps, env.clone(), "bob", VoteOption::No).unwrap();
+    let tally = query_tally(&deps).tally;
+    assert_eq!(tally.yes, Uint128::new(600));
+    assert_eq!(tally.no, Uint128::new(400));
+    assert_eq!(tally.voters, 2);
+
+    // voting again replaces the previous vote
+    vote(&mut deps, env.clone(), "alice", VoteOption::Abstain).unwrap();
+    let tally = query_tally(&deps).tally;
+    assert!(tally.yes.is_zero());
+    assert_eq!(tally.abstain, Uint128::new(600));
+    assert_eq!(tally.voters, 2);
+
+    // tokens moved after the registration can't vote again
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        ExecuteMsg::UnlockVotingPower {
+            amount: Uint128::new(600),
+        },
+    )
+    .unwrap();
+    let info = mock_info("carol", &coins(600, LIQUID_DENOM));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::LockVotingPower {},
+    )
+    .unwrap();
+    let err = vote(&mut deps, env.clone(), "carol", VoteOption::Yes).unwrap_err();
+    assert!(matches!(err, ContractError::NoVotingPower {}));
+    assert_eq!(query_tally(&deps).tally.total(), Uint128::new(1000));
+
+    let cast = ExecuteMsg::CastVote { proposal_id: 1 };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        cast.clone(),
+    )
+    .unwrap();
+    let weighted = GovMsg::VoteWeighted {
+        proposal_id: 1,
+        options: vec![
+            WeightedVoteOption {
+                option: VoteOption::No,
+                weight: Decimal::percent(40),
+            },
+            WeightedVoteOption {
+                option: VoteOption::Abstain,
+                weight: Decimal::percent(60),
+            },
+        ],
+    };
+    assert_eq!(res.messages, vec![SubMsg::new(weighted)]);
+
+    // nothing can be voted or cast once the voting period is over
+    env.block.time = voting_end;
+    let err = vote(&mut deps, env.clone(), "bob", VoteOption::Yes).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::GovVotingClosed { proposal_id: 1 }
+    ));
+    let err = execute(deps.as_mut(), env, mock_info("anyone", &[]), cast).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::GovVotingClosed { proposal_id: 1 }
+    ));
+}
+
+#[test]
+fn validator_proposal_quorum_counts_issued_tokens() {
+    let mut deps = mock_deps();
+    let mut msg = instantiate_msg(LiquidTokenInfo::TokenFactory {
+        subdenom: SUBDENOM.to_string(),
+    });
+    msg.validator_gov = Some(ValidatorGovConfig {
+        voting_period: 3600,
+        timelock: 0,
+        quorum: Decimal::percent(20),
+        threshold: Decimal::percent(50),
+    });
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    stake(&mut deps, "alice", 1000);
+    let info = mock_info("alice", &coins(100, LIQUID_DENOM));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::LockVotingPower {},
+    )
+    .unwrap();
+
+    // voting power is taken at the start of the block the proposal is created in
+    let mut env = mock_env();
+    env.block.height += 1;
+    let changes = vec![ValidatorChange::SetWeight {
+        address: VALIDATOR.to_string(),
+        weight: 2,
+    }];
+    let propose = ExecuteMsg::ProposeValidatorChanges { changes };
+    execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose).unwrap();
+    let ballot = ExecuteMsg::VoteValidatorProposal {
+        proposal_id: 1,
+        ballot: Ballot::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), ballot).unwrap();
+
+    // every locked token voted yes, but they are only a tenth of the liquid tokens issued
+    env.block.time = env.block.time.plus_seconds(3600);
+    let query_msg = QueryMsg::ValidatorProposal { proposal_id: 1 };
+    let res: ValidatorProposalResponse =
+        from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+    assert_eq!(res.proposal.total_power, Uint128::new(1000));
+    assert_eq!(res.proposal.yes, Uint128::new(100));
+    assert_eq!(res.status, ProposalStatus::Rejected);
+}
+
+// harvest runs a harvest at `env` that claims `rewards` native tokens, on top of `balance` already held
+fn harvest(
+    deps: &mut MockDeps,
+    env: Env,
+    balance: u128,
+    rewards: u128,
+) -> cosmwasm_std::Response<BindingMsg> {
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(balance, DENOM));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("harvester", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+    let process = res.messages.last().unwrap();
+    assert_eq!(process.reply_on, ReplyOn::Success);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(balance + rewards, DENOM));
+    let process_reply = Reply {
+        id: process.id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), env, process_reply).unwrap()
+}
+
+fn query_under_unstaking(deps: &MockDeps, address: &str) -> Uint128 {
+    let query_msg = QueryMsg::UnderUnstakingOf {
+        address: address.to_string(),
+    };
+    let res: BalanceResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    res.balance
+}
+
+#[test]
+fn liquid_queue_pays_epoch_undelegation() {
+    let mut deps = mock_deps();
+    let mut msg = instantiate_msg(LiquidTokenInfo::TokenFactory {
+        subdenom: SUBDENOM.to_string(),
+    });
+    msg.queue_mode = QueueMode::Liquid;
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    stake(&mut deps, "staker", 1000);
+    unstake_denom(&mut deps, &coins(400, LIQUID_DENOM)).unwrap();
+    // the request follows the exchange rate while its epoch is open
+    assert_eq!(query_under_unstaking(&deps, "staker"), Uint128::new(400));
+
+    // the epoch closes after rewards grew the exchange rate by 10%
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(UNBONDING_PERIOD / 6);
+    env.block.height += 1;
+    let res = harvest(&mut deps, env.clone(), 0, 100);
+    assert!(res.messages.iter().any(|m| m.msg
+        == StakingMsg::Undelegate {
+            validator: VALIDATOR.to_string(),
+            amount: coin(440, DENOM),
+        }
+        .into()));
+    let undelegation = UNDELEGATIONS.load(&deps.storage, 0).unwrap();
+    assert_eq!(undelegation.liquid, Uint128::new(400));
+    assert_eq!(undelegation.units, Uint128::new(440));
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(660));
+    assert_eq!(supply.unstaking_liquid, Uint128::zero());
+    assert_eq!(supply.unstakings, Uint128::new(440));
+    set_delegation(&mut deps, 660);
+
+    // rewards earned after the undelegation don't grow the request
+    env.block.time = env.block.time.plus_seconds(60);
+    env.block.height += 1;
+    harvest(&mut deps, env.clone(), 0, 66);
+    assert_eq!(query_under_unstaking(&deps, "staker"), Uint128::new(440));
+    set_delegation(&mut deps, 726);
+
+    // the matured undelegation pays the request in full
+    env.block.time = undelegation.completion;
+    env.block.height += 1;
+    harvest(&mut deps, env, 440, 0);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.unstakings, Uint128::zero());
+    assert_eq!(supply.unstaking_units, Uint128::zero());
+    assert_eq!(supply.claims, Uint128::new(440));
+    assert_eq!(query_under_unstaking(&deps, "staker"), Uint128::zero());
+}
+
+fn update_index(deps: &mut MockDeps, holder: &str) {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(holder, &[]),
+        ExecuteMsg::UpdateIndex {},
+    )
+    .unwrap();
+}
+
+fn query_rewards(deps: &MockDeps, address: &str) -> Uint128 {
+    let query_msg = QueryMsg::RewardsOf {
+        address: address.to_string(),
+    };
+    let res: RewardsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    res.rewards
+}
+
+fn lock(deps: &mut MockDeps, holder: &str, amount: u128) {
+    let info = mock_info(holder, &coins(amount, LIQUID_DENOM));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::LockVotingPower {},
+    )
+    .unwrap();
+}
+
+fn claim_rewards(
+    deps: &mut MockDeps,
+    holder: &str,
+) -> Result<cosmwasm_std::Response<BindingMsg>, ContractError> {
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(holder, &[]),
+        ExecuteMsg::ClaimRewards {},
+    )
+}
+
+#[test]
+fn rewards_accrue_on_locked_tokens_only() {
+    let mut deps = mock_deps();
+    let mut msg = instantiate_msg(LiquidTokenInfo::TokenFactory {
+        subdenom: SUBDENOM.to_string(),
+    });
+    msg.reward_mode = RewardMode::Index;
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    stake(&mut deps, "alice", 1000);
+    deps.querier
+        .update_balance("alice", coins(1000, LIQUID_DENOM));
+    update_index(&mut deps, "alice");
+    // alice sends the liquid tokens to bob, who locks them
+    deps.querier.update_balance("alice", vec![]);
+    deps.querier
+        .update_balance("bob", coins(1000, LIQUID_DENOM));
+    update_index(&mut deps, "bob");
+    lock(&mut deps, "bob", 1000);
+
+    let mut env = mock_env();
+    env.block.height += 1;
+    harvest(&mut deps, env, 0, 100);
+    let res = claim_rewards(&mut deps, "bob").unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        BankMsg::Send {
+            to_address: "bob".to_string(),
+            amount: coins(100, DENOM),
+        }
+        .into()
+    );
+
+    // bob unlocks and sends the tokens back, alice can't claim the same rewards again
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bob", &[]),
+        ExecuteMsg::UnlockVotingPower {
+            amount: Uint128::new(1000),
+        },
+    )
+    .unwrap();
+    deps.querier.update_balance("bob", vec![]);
+    deps.querier
+        .update_balance("alice", coins(1000, LIQUID_DENOM));
+    update_index(&mut deps, "alice");
+    lock(&mut deps, "alice", 1000);
+    let err = claim_rewards(&mut deps, "alice").unwrap_err();
+    assert!(matches!(err, ContractError::NoRewards {}));
+    let err = claim_rewards(&mut deps, "bob").unwrap_err();
+    assert!(matches!(err, ContractError::NoRewards {}));
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.rewards, Uint128::zero());
+}
+
+#[test]
+fn index_mode_keeps_peg() {
+    let mut deps = mock_deps_with_records(vec![rewards_record(1, coins(50, DENOM))]);
+    let mut msg = instantiate_msg(LiquidTokenInfo::TokenFactory {
+        subdenom: SUBDENOM.to_string(),
+    });
+    msg.reward_mode = RewardMode::Index;
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    // rewards earned before anyone locked liquid tokens wait for the first holders
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::WithdrawArchwayRewards {},
+    )
+    .unwrap();
+    stake(&mut deps, "alice", 1000);
+    lock(&mut deps, "alice", 1000);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(1000));
+    assert_eq!(supply.issued, Uint128::new(1000));
+    assert_eq!(supply.rewards, Uint128::new(50));
+
+    let mut env = mock_env();
+    env.block.height += 1;
+    harvest(&mut deps, env.clone(), 50, 100);
+    assert_eq!(query_rewards(&deps, "alice"), Uint128::new(150));
+
+    // a slashing loss is made up for by the next rewards instead of lowering the ratio
+    set_delegation(&mut deps, 800);
+    env.block.height += 1;
+    let res = harvest(&mut deps, env.clone(), 150, 0);
+    assert!(res.events.iter().any(|e| e.ty == "slashing_detected"));
+    env.block.height += 1;
+    harvest(&mut deps, env.clone(), 150, 60);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(1000));
+    assert_eq!(supply.peg_shortfall, Uint128::new(140));
+    assert_eq!(supply.rewards, Uint128::new(150));
+    assert_eq!(query_rewards(&deps, "alice"), Uint128::new(150));
+
+    let query_msg = QueryMsg::SimulateStake {
+        amount: Uint128::new(333),
+    };
+    let res: SimulateStakeResponse =
+        from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+    assert_eq!(res.minted, Uint128::new(333));
+    assert_eq!(res.ratio, Decimal::one());
+}
+
+#[test]
+fn rebalance_withdraws_rewards_before_moving_stake() {
+    let mut deps = mock_deps();
+    let mut msg = instantiate_msg(LiquidTokenInfo::TokenFactory {
+        subdenom: SUBDENOM.to_string(),
+    });
+    msg.rebalance_threshold = Decimal::percent(10);
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    stake(&mut deps, "alice", 1000);
+
+    // the only validator holding stake is removed from the set
+    let other = Validator {
+        address: "other".to_string(),
+        ..mock_validator()
+    };
+    let delegation = FullDelegation {
+        delegator: Addr::unchecked(MOCK_CONTRACT_ADDR),
+        validator: VALIDATOR.to_string(),
+        amount: coin(1000, DENOM),
+        can_redelegate: coin(1000, DENOM),
+        accumulated_rewards: vec![],
+    };
+    deps.querier
+        .update_staking(DENOM, &[mock_validator(), other], &[delegation]);
+    let validators = vec![ValidatorInfo {
+        address: "other".to_string(),
+        weight: 1,
+    }];
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateValidators { validators },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("harvester", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+    let withdraw = DistributionMsg::WithdrawDelegatorReward {
+        validator: VALIDATOR.to_string(),
+    };
+    let redelegate = StakingMsg::Redelegate {
+        src_validator: VALIDATOR.to_string(),
+        dst_validator: "other".to_string(),
+        amount: coin(1000, DENOM),
+    };
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages[0], SubMsg::new(withdraw));
+    assert_eq!(res.messages[1].msg, redelegate.into());
+    assert_eq!(res.messages[1].reply_on, ReplyOn::Success);
+}
+
+#[test]
+fn redelegate_credits_withdrawn_rewards() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "alice", 1000);
+    let other = Validator {
+        address: "other".to_string(),
+        ..mock_validator()
+    };
+    let delegation = FullDelegation {
+        delegator: Addr::unchecked(MOCK_CONTRACT_ADDR),
+        validator: VALIDATOR.to_string(),
+        amount: coin(1000, DENOM),
+        can_redelegate: coin(1000, DENOM),
+        accumulated_rewards: vec![],
+    };
+    deps.querier
+        .update_staking(DENOM, &[mock_validator(), other], &[delegation]);
+    let validators = vec![
+        ValidatorInfo {
+            address: VALIDATOR.to_string(),
+            weight: 1,
+        },
+        ValidatorInfo {
+            address: "other".to_string(),
+            weight: 1,
+        },
+    ];
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateValidators { validators },
+    )
+    .unwrap();
+
+    let redelegate = |amount: u128| ExecuteMsg::Redelegate {
+        src: VALIDATOR.to_string(),
+        dst: "other".to_string(),
+        amount: Uint128::new(amount),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        redelegate(0),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::ZeroRedelegation {}));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        redelegate(400),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Success);
+
+    // the redelegation withdrew 50 native tokens of rewards
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(50, DENOM));
+    let process_reply = Reply {
+        id: res.messages[0].id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), process_reply).unwrap();
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(1050));
+}
+
+#[test]
+fn matured_requests_past_limit_keep_their_funds() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+    for _ in 0..60 {
+        unstake_denom(&mut deps, &coins(10, LIQUID_DENOM)).unwrap();
+    }
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(UNBONDING_PERIOD / 6);
+    env.block.height += 1;
+    harvest(&mut deps, env.clone(), 0, 0);
+    let undelegation = UNDELEGATIONS.load(&deps.storage, 0).unwrap();
+    assert_eq!(undelegation.amount, Uint128::new(600));
+    set_delegation(&mut deps, 400);
+
+    // the first process pays 50 requests and keeps the rest of the undelegation for the others
+    env.block.time = undelegation.completion;
+    env.block.height += 1;
+    let res = harvest(&mut deps, env.clone(), 600, 0);
+    assert!(!res
+        .messages
+        .iter()
+        .any(|m| matches!(m.msg, CosmosMsg::Staking(StakingMsg::Delegate { .. }))));
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.claims, Uint128::new(500));
+    assert_eq!(supply.unstakings, Uint128::new(100));
+
+    env.block.height += 1;
+    harvest(&mut deps, env, 600, 0);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.claims, Uint128::new(600));
+    assert_eq!(supply.unstakings, Uint128::zero());
+}
+
+// state written by v0.1.0, before the migration
+#[derive(Serialize, Deserialize)]
+struct LegacyConfig {
+    owner: Addr,
+    bond_denom: String,
+    liquid_token_addr: Addr,
+    validator: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LegacySupply {
+    native: Uint128,
+    unstakings: Uint128,
+    claims: Uint128,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LegacyLinkedList {
+    head_id: u64,
+    tail_id: u64,
+    length: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LegacyNode {
+    receiver: Addr,
+    value: Uint128,
+    height: u64,
+    prev: u64,
+    next: u64,
+}
+
+#[test]
+fn migration_undelegates_for_legacy_queue() {
+    let mut deps = mock_deps();
+    set_contract_version(&mut deps.storage, "crates.io:liquid-staking", "0.1.0").unwrap();
+    let config = LegacyConfig {
+        owner: Addr::unchecked("owner"),
+        bond_denom: DENOM.to_string(),
+        liquid_token_addr: Addr::unchecked("token"),
+        validator: VALIDATOR.to_string(),
+    };
+    Item::new("config")
+        .save(&mut deps.storage, &config)
+        .unwrap();
+    let supply = LegacySupply {
+        native: Uint128::new(700),
+        unstakings: Uint128::new(300),
+        claims: Uint128::zero(),
+    };
+    Item::new("total_supply")
+        .save(&mut deps.storage, &supply)
+        .unwrap();
+    let list = LegacyLinkedList {
+        head_id: 1,
+        tail_id: 1,
+        length: 1,
+    };
+    singleton(&mut deps.storage, b"linked_list")
+        .save(&list)
+        .unwrap();
+    let node = LegacyNode {
+        receiver: Addr::unchecked("alice"),
+        value: Uint128::new(300),
+        height: 1,
+        prev: 0,
+        next: 0,
+    };
+    bucket(&mut deps.storage, b"node")
+        .save(&1u64.to_be_bytes(), &node)
+        .unwrap();
+    UNDER_UNSTAKING
+        .save(&mut deps.storage, &node.receiver, &node.value)
+        .unwrap();
+    deps.querier.update_wasm(|_| {
+        let token_info = TokenInfoResponse {
+            name: "liquid".to_string(),
+            symbol: "STK".to_string(),
+            decimals: 6,
+            total_supply: Uint128::new(700),
+        };
+        SystemResult::Ok(ContractResult::Ok(to_binary(&token_info).unwrap()))
+    });
+    // 100 of the request was never undelegated, 50 already landed and 150 is still in flight
+    set_delegation(&mut deps, 800);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(50, DENOM));
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(StakingMsg::Undelegate {
+            validator: VALIDATOR.to_string(),
+            amount: coin(300, DENOM),
+        })]
+    );
+    assert_eq!(LINKED_LIST.load(&deps.storage).unwrap().last_id, 1);
+    let undelegation = UNDELEGATIONS.load(&deps.storage, 0).unwrap();
+    assert_eq!(undelegation.amount, Uint128::new(450));
+    assert_eq!(
+        undelegation.completion,
+        mock_env().block.time.plus_seconds(UNBONDING_PERIOD)
+    );
+
+    // the legacy tokens are delegated again once landed, without any slashing detected
+    set_delegation(&mut deps, 500);
+    let mut env = mock_env();
+    env.block.height += 1;
+    let res = harvest(&mut deps, env.clone(), 200, 0);
+    assert!(res.messages.iter().any(|m| m.msg
+        == CosmosMsg::Staking(StakingMsg::Delegate {
+            validator: VALIDATOR.to_string(),
+            amount: coin(200, DENOM),
+        })));
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.native, Uint128::new(700));
+    assert_eq!(supply.unstakings, Uint128::new(300));
+
+    // the request is paid by the undelegation sent at the migration
+    set_delegation(&mut deps, 700);
+    env.block.time = undelegation.completion;
+    env.block.height += 1;
+    harvest(&mut deps, env, 300, 0);
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.claims, Uint128::new(300));
+    assert_eq!(supply.unstakings, Uint128::zero());
+    assert_eq!(supply.native, Uint128::new(700));
+}
+
+// set_token_supply makes the cw20 liquid token report `amount` tokens issued
+fn set_token_supply(deps: &mut MockDeps, amount: u128) {
+    deps.querier.update_wasm(move |_| {
+        let token_info = TokenInfoResponse {
+            name: "Staked Const".to_string(),
+            symbol: "STKCONST".to_string(),
+            decimals: 6,
+            total_supply: Uint128::new(amount),
+        };
+        SystemResult::Ok(ContractResult::Ok(to_binary(&token_info).unwrap()))
+    });
+}
+
+#[test]
+fn liquid_unstake_excludes_pending_cw20_burn() {
+    let mut deps = mock_deps();
+    let mut msg = instantiate_msg(LiquidTokenInfo::Cw20 {
+        code_id: 1,
+        name: "Staked Const".to_string(),
+        symbol: "STKCONST".to_string(),
+        decimals: 6,
+    });
+    msg.queue_mode = QueueMode::Liquid;
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let mut data = vec![0x0a, "liquidtoken".len() as u8];
+    data.extend_from_slice(b"liquidtoken");
+    let token_reply = Reply {
+        id: res.messages[0].id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(Binary::from(data)),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), token_reply).unwrap();
+    set_token_supply(&mut deps, 0);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1000, DENOM));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("staker", &coins(1000, DENOM)),
+        ExecuteMsg::Stake { recipient: None },
+    )
+    .unwrap();
+    set_token_supply(&mut deps, 1000);
+
+    // nothing is delegated yet, the epoch closes before the burn of the unstaked tokens ran
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(UNBONDING_PERIOD / 6);
+    let receive = Cw20ReceiveMsg {
+        sender: "staker".to_string(),
+        amount: Uint128::new(100),
+        msg: to_binary(&ReceiveMsg::Unstake { recipient: None }).unwrap(),
+    };
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("liquidtoken", &[]),
+        ExecuteMsg::Receive(receive),
+    )
+    .unwrap();
+    let supply = TOTAL_SUPPLY.load(&deps.storage).unwrap();
+    assert_eq!(supply.unstakings, Uint128::new(100));
+    assert_eq!(supply.native, Uint128::new(900));
+}
+
+#[test]
+fn request_ids_are_never_reused() {
+    let mut deps = mock_deps();
+    instantiate_token_factory(&mut deps);
+    stake(&mut deps, "staker", 1000);
+    unstake_denom(&mut deps, &coins(100, LIQUID_DENOM)).unwrap();
+    let cancel = ExecuteMsg::CancelUnstake { request_id: 1 };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("staker", &[]),
+        cancel.clone(),
+    )
+    .unwrap();
+    assert_eq!(LINKED_LIST.load(&deps.storage).unwrap().length, 0);
+
+    // the emptied queue hands out a new id, the cancelled one can't reach the new request
+    unstake_denom(&mut deps, &coins(100, LIQUID_DENOM)).unwrap();
+    assert!(NODES.has(&deps.storage, 2));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("staker", &[]), cancel).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::UnstakeRequestNotFound { id: 1 }
+    ));
+    assert!(NODES.has(&deps.storage, 2));
+}
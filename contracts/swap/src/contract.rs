@@ -2,21 +2,24 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     coins, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, 
-    QueryRequest, WasmQuery, Response, StdError, StdResult, Uint128, 
+    QueryRequest, WasmMsg, WasmQuery, Response, StdError, StdResult, Uint128, 
 };
 
-use cw2::set_contract_version;
-use cw20::{BalanceResponse, Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg, 
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20ReceiveMsg, 
     };
 
-use crate::linked_list::{LinkedList, NodeWithId, node_read, node_update_value, 
-    linked_list, linked_list_read, linked_list_append, linked_list_remove_head, 
-    linked_list_remove, linked_list_get_list};
+use crate::bindings::{query_rewards_records, BindingMsg, BindingQuery};
+use crate::linked_list::{LinkedList, NodeWithId, LINKED_LIST, NODES, node_update_value, 
+    linked_list_append, linked_list_remove_head, linked_list_remove, linked_list_get_list};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, ConfigResponse, StatusResponse, InstantiateMsg, QueryMsg, 
+use crate::migration::migrate_from_v0_1;
+use crate::msg::{ExecuteMsg, MigrateMsg, ConfigResponse, StatusResponse, InstantiateMsg, QueryMsg, 
     OrderInfoOfResponse, OrderBookResponse, StakingManagerQueryMsg, 
     StakingManagerStatusResponse};
-use crate::state::{ConfigInfo, Supply, CONFIG, TOTAL_SUPPLY, CLAIMABLE, QUEUE_ID};
+use crate::state::{ConfigInfo, OwnershipProposal, Supply, CONFIG, TOTAL_SUPPLY, CLAIMABLE, QUEUE_ID,
+    OWNERSHIP_PROPOSAL};
 
 const FALLBACK_RATIO: Decimal = Decimal::one();
 
@@ -24,13 +27,16 @@ const FALLBACK_RATIO: Decimal = Decimal::one();
 const CONTRACT_NAME: &str = "crates.io:liquid-swap";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// number of Archway rewards records withdrawn at most at once
+const ARCHWAY_RECORDS_LIMIT: u64 = 100;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let linked_list_init = LinkedList {
@@ -38,7 +44,7 @@ pub fn instantiate(
         tail_id: 0,
         length: 0
     };
-    linked_list(deps.storage).save(&linked_list_init)?;
+    LINKED_LIST.save(deps.storage, &linked_list_init)?;
 
     let denom = deps.querier.query_bonded_denom()?;
     let config_init = ConfigInfo {
@@ -59,16 +65,20 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     match msg {
         ExecuteMsg::Add {} => execute_add(deps, env, info),
         ExecuteMsg::Remove {} => execute_remove(deps, env, info),
         ExecuteMsg::Claim {} => execute_claim(deps, info),
+        ExecuteMsg::WithdrawArchwayRewards {} => execute_withdraw_archway_rewards(deps, env, info),
         ExecuteMsg::SetSwapFee { swap_fee } => execute_set_swap_fee(deps, info, swap_fee),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => execute_propose_new_owner(deps, env, info, owner, expires_in),
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => execute_cancel_ownership_proposal(deps, info),
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
     }
 }
@@ -81,7 +91,7 @@ fn get_ratio(numerator: Uint128, denominator: Uint128) -> Decimal {
     }
 }
 
-pub fn execute_add(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn execute_add(deps: DepsMut<BindingQuery>, env: Env, info: MessageInfo) -> Result<Response<BindingMsg>, ContractError> {
     // ensure we have the proper denom
     let config = CONFIG.load(deps.storage)?;
     // payment finds the proper coin (or throws an error)
@@ -113,8 +123,7 @@ pub fn execute_add(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respons
     // update node id of user in the queue
     let old_node_id = QUEUE_ID.may_load(deps.storage, &info.sender)?.unwrap_or_default();
     if old_node_id > 0 {
-        let old_node_key = &old_node_id.to_be_bytes();
-        let old_node = node_read(deps.storage).load(old_node_key)?;
+        let old_node = NODES.load(deps.storage, old_node_id)?;
         new_node_value += old_node.value;
         linked_list_remove(deps.storage, old_node_id)?;
     }
@@ -127,7 +136,7 @@ pub fn execute_add(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respons
     Ok(res)
 }
 
-pub fn execute_remove(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn execute_remove(deps: DepsMut<BindingQuery>, env: Env, info: MessageInfo) -> Result<Response<BindingMsg>, ContractError> {
     // ensure we have the proper denom
     let config = CONFIG.load(deps.storage)?;
 
@@ -135,9 +144,7 @@ pub fn execute_remove(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Resp
     if node_id == 0 {
         return Err(ContractError::NothingToRemove {});
     }
-
-    let node_key = &node_id.to_be_bytes();
-    let cur_node = node_read(deps.storage).load(node_key)?;
+    let cur_node = NODES.load(deps.storage, node_id)?;
     linked_list_remove(deps.storage, node_id)?;
     QUEUE_ID.save(deps.storage, &info.sender, &0)?;
     let balance = deps
@@ -162,9 +169,9 @@ pub fn execute_remove(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Resp
 }
 
 pub fn execute_claim(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     info: MessageInfo,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let to_send = CLAIMABLE.may_load(deps.storage, &info.sender)?.unwrap_or_default();
     if to_send == Uint128::zero() {
@@ -178,12 +185,15 @@ pub fn execute_claim(
     })?;
 
     // transfer liquid token
-    let cw20 = Cw20Contract(config.liquid_token_addr);
     // Build a cw20 transfer send msg, that send collected funds to target address
-    let msg = cw20.call(Cw20ExecuteMsg::Transfer {
-        recipient: info.sender.to_string(),
-        amount: to_send,
-    })?;
+    let msg = WasmMsg::Execute {
+        contract_addr: config.liquid_token_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount: to_send,
+        })?,
+        funds: vec![],
+    };
     
     // transfer tokens to the sender
     let res = Response::new()
@@ -195,11 +205,11 @@ pub fn execute_claim(
 }
 
 pub fn execute_receive(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     env: Env,
     info: MessageInfo,
     wrapper: Cw20ReceiveMsg,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     // info.sender is the address of the cw20 contract (that re-sent this message).
     // wrapper.sender is the address of the user that requested the cw20 contract to send this.
     // This cannot be fully trusted (the cw20 contract can fake it), so only use it for actions
@@ -216,11 +226,11 @@ pub fn execute_receive(
 }
 
 pub fn execute_swap(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     env: Env,
     sender: Addr,
     amount: Uint128,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     let contract_addr = env.contract.address;
     let config = CONFIG.load(deps.storage)?;
 
@@ -250,10 +260,9 @@ pub fn execute_swap(
     let mut remain_lp_token = order_lp_token_value;
     while !is_filled {
         // Get next order from the queue
-        let linked_list_info = linked_list_read(deps.storage).load()?;
+        let linked_list_info = LINKED_LIST.load(deps.storage)?;
         let counterparty_id = linked_list_info.head_id;
-        let counterparty_key = &counterparty_id.to_be_bytes();
-        let counterparty_order = node_read(deps.storage).load(counterparty_key)?;
+        let counterparty_order = NODES.load(deps.storage, counterparty_id)?;
         let counterparty_address = counterparty_order.receiver;
         let counterparty_lp_amount = counterparty_order.value;
         let mut counterparty_filled = false;
@@ -298,11 +307,41 @@ pub fn execute_swap(
     Ok(res)
 }
 
+// withdraw the Archway rewards of this contract, they join the pool balance shared by liquidity providers
+pub fn execute_withdraw_archway_rewards(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let records = query_rewards_records(&deps.querier, env.contract.address.to_string(), ARCHWAY_RECORDS_LIMIT)?;
+    if records.is_empty() {
+        return Err(ContractError::NoArchwayRewards {});
+    }
+    let rewards: Uint128 = records
+        .iter()
+        .flat_map(|record| record.rewards.iter())
+        .filter(|reward| reward.denom == config.bond_denom)
+        .map(|reward| reward.amount)
+        .sum();
+
+    let res = Response::new()
+        .add_message(BindingMsg::WithdrawRewards {
+            records_limit: None,
+            record_ids: records.iter().map(|record| record.id).collect(),
+        })
+        .add_attribute("action", "withdrawArchwayRewards")
+        .add_attribute("from", info.sender)
+        .add_attribute("records", records.len().to_string())
+        .add_attribute("rewards", rewards);
+    Ok(res)
+}
+
 pub fn execute_set_swap_fee(
-    deps: DepsMut,
+    deps: DepsMut<BindingQuery>,
     info: MessageInfo,
     swap_fee: Uint128,
-) -> Result<Response, ContractError> {
+) -> Result<Response<BindingMsg>, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
     // only allow liquid token contract to call 
     if info.sender != config.owner {
@@ -318,8 +357,110 @@ pub fn execute_set_swap_fee(
     Ok(res)
 }
 
+pub fn execute_propose_new_owner(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires_in: Option<u64>,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let proposal = OwnershipProposal {
+        owner: deps.api.addr_validate(&owner)?,
+        expires: expires_in.map(|seconds| env.block.time.plus_seconds(seconds)),
+    };
+    // a new proposal replaces the pending one
+    OWNERSHIP_PROPOSAL.save(deps.storage, &proposal)?;
+
+    let res = Response::new()
+        .add_attribute("action", "proposeNewOwner")
+        .add_attribute("from", info.sender)
+        .add_attribute("owner", proposal.owner);
+    Ok(res)
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut<BindingQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let proposal = OWNERSHIP_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoOwnershipProposal {})?;
+    // only allow proposed owner to call 
+    if info.sender != proposal.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if proposal.expires.is_some_and(|expires| env.block.time >= expires) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+    let previous_owner = CONFIG.load(deps.storage)?.owner;
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        config.owner = proposal.owner;
+        Ok(config)
+    })?;
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    let res = Response::new()
+        .add_attribute("action", "acceptOwnership")
+        .add_attribute("from", info.sender)
+        .add_attribute("previous_owner", previous_owner);
+    Ok(res)
+}
+
+pub fn execute_cancel_ownership_proposal(
+    deps: DepsMut<BindingQuery>,
+    info: MessageInfo,
+) -> Result<Response<BindingMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only allow owner to call 
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if OWNERSHIP_PROPOSAL.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoOwnershipProposal {});
+    }
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
+    let res = Response::new()
+        .add_attribute("action", "cancelOwnershipProposal")
+        .add_attribute("from", info.sender);
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut<BindingQuery>, _env: Env, _msg: MigrateMsg) -> Result<Response<BindingMsg>, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract { contract: stored.contract });
+    }
+    let stored_version: Version = stored.version.parse()?;
+    let version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > version {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            version: CONTRACT_VERSION.to_string(),
+        });
+    }
+    // run the state transforms of every version between the stored one and this one
+    if stored_version < Version::new(0, 2, 0) {
+        migrate_from_v0_1(deps.storage)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+    Ok(res)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps<BindingQuery>, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::ClaimableOf { address } => {
             to_binary(&query_claimable_of(deps, address)?)
@@ -333,7 +474,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     }
 }
 
-pub fn query_claimable_of(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+pub fn query_claimable_of(deps: Deps<BindingQuery>, address: String) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
     let claimable = CLAIMABLE
         .may_load(deps.storage, &address)?
@@ -341,8 +482,9 @@ pub fn query_claimable_of(deps: Deps, address: String) -> StdResult<BalanceRespo
     Ok(BalanceResponse { balance: claimable })
 }
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+pub fn query_config(deps: Deps<BindingQuery>) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
+    let proposal = OWNERSHIP_PROPOSAL.may_load(deps.storage)?;
 
     let res = ConfigResponse {
         owner: config.owner.to_string(),
@@ -350,11 +492,13 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         liquid_token_addr: config.liquid_token_addr.to_string(),
         staking_manager_addr: config.staking_manager_addr.to_string(),
         swap_fee: config.swap_fee,
+        pending_owner: proposal.as_ref().map(|p| p.owner.to_string()),
+        pending_owner_expires: proposal.and_then(|p| p.expires),
     };
     Ok(res)
 }
 
-pub fn query_status(deps: Deps, _env: Env) -> StdResult<StatusResponse> {
+pub fn query_status(deps: Deps<BindingQuery>, _env: Env) -> StdResult<StatusResponse> {
     let config = CONFIG.load(deps.storage)?;
     let supply = TOTAL_SUPPLY.load(deps.storage)?;
 
@@ -371,7 +515,7 @@ pub fn query_status(deps: Deps, _env: Env) -> StdResult<StatusResponse> {
     Ok(res)
 }
 
-pub fn query_order_info_of(deps: Deps, _env: Env, address: String) -> StdResult<OrderInfoOfResponse> {
+pub fn query_order_info_of(deps: Deps<BindingQuery>, _env: Env, address: String) -> StdResult<OrderInfoOfResponse> {
     let config = CONFIG.load(deps.storage)?;
     let supply = TOTAL_SUPPLY.load(deps.storage)?;
 
@@ -386,8 +530,7 @@ pub fn query_order_info_of(deps: Deps, _env: Env, address: String) -> StdResult<
     let mut issued = Uint128::zero();
     let mut height = 0;
     if node_id > 0 {
-        let node_key = &node_id.to_be_bytes();
-        let cur_node = node_read(deps.storage).load(node_key)?;
+        let cur_node = NODES.load(deps.storage, node_id)?;
         issued = cur_node.value;
         height = cur_node.height;
     }
@@ -396,8 +539,8 @@ pub fn query_order_info_of(deps: Deps, _env: Env, address: String) -> StdResult<
     Ok(OrderInfoOfResponse { issued, native, height, node_id})
 }
 
-pub fn query_order_book(deps: Deps) -> StdResult<OrderBookResponse> {
-    let state = linked_list_read(deps.storage).load()?;
+pub fn query_order_book(deps: Deps<BindingQuery>) -> StdResult<OrderBookResponse> {
+    let state = LINKED_LIST.load(deps.storage)?;
 
     let unstaking_requests: Vec<NodeWithId> = linked_list_get_list(deps.storage, 50)?;
 
@@ -6,6 +6,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
     
+    #[error("{0}")]
+    SemVer(#[from] semver::Error),
+
     #[error("Unauthorized")]
     Unauthorized {},
     // Add any other custom errors you like here.
@@ -20,6 +23,21 @@ pub enum ContractError {
     #[error("No claims that can be released currently")]
     NothingToClaim {},
 
+    #[error("No ownership transfer is pending")]
+    NoOwnershipProposal {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
+    #[error("Can't migrate from contract '{contract}'")]
+    InvalidMigrationContract { contract: String },
+
+    #[error("Can't migrate from version {stored} to older version {version}")]
+    CannotDowngrade { stored: String, version: String },
+
+    #[error("No Archway rewards to withdraw")]
+    NoArchwayRewards {},
+
     #[error("User is not a liquidity provider to remove")]
     NothingToRemove{},
 
@@ -1,7 +1,12 @@
+pub mod bindings;
 pub mod contract;
 pub mod linked_list;
 mod error;
+pub mod migration;
 pub mod msg;
 pub mod state;
 
+#[cfg(test)]
+mod testing;
+
 pub use crate::error::ContractError;
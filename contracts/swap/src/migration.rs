@@ -0,0 +1,26 @@
+use cosmwasm_std::{StdResult, Storage};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read};
+
+use crate::linked_list::{LinkedList, Node, LINKED_LIST, NODES};
+
+// v0.1.0 stored the order book with cosmwasm_storage
+static LEGACY_NODE_KEY: &[u8] = b"node";
+static LEGACY_LINKED_LIST_KEY: &[u8] = b"linked_list";
+
+// migrate_from_v0_1 upgrades the state written by v0.1.0
+pub fn migrate_from_v0_1(storage: &mut dyn Storage) -> StdResult<()> {
+    // order book: move the cosmwasm_storage buckets into cw-storage-plus maps
+    let state: LinkedList = singleton_read(storage, LEGACY_LINKED_LIST_KEY).load()?;
+    let mut node_id = state.head_id;
+    for _ in 0..state.length {
+        let key = node_id.to_be_bytes();
+        let node: Node = bucket_read(storage, LEGACY_NODE_KEY).load(&key)?;
+        NODES.save(storage, node_id, &node)?;
+        bucket::<Node>(storage, LEGACY_NODE_KEY).remove(&key);
+        node_id = node.next;
+    }
+    LINKED_LIST.save(storage, &state)?;
+    singleton::<LinkedList>(storage, LEGACY_LINKED_LIST_KEY).remove();
+
+    Ok(())
+}
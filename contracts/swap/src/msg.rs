@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Uint128, Decimal, Coin};
+use cosmwasm_std::{Uint128, Decimal, Coin, Timestamp};
 use cw20::{Cw20ReceiveMsg};
 
 use crate::linked_list::{NodeWithId, LinkedList};
@@ -23,14 +23,26 @@ pub enum ExecuteMsg {
     Remove {},
     /// Claim is called by liquidity provider to claim liquid token from swapping
     Claim {},
+    /// WithdrawArchwayRewards withdraws the Archway gas rebates and premiums of this contract into
+    /// the pool, anyone can call it
+    WithdrawArchwayRewards {},
     /// Admin call this method to set up swap fee
     SetSwapFee { swap_fee: Uint128 },
+    /// Admin call this method to propose a new owner, the proposal can expire after `expires_in` seconds
+    ProposeNewOwner { owner: String, expires_in: Option<u64> },
+    /// Proposed owner call this method to become the owner
+    AcceptOwnership {},
+    /// Admin call this method to cancel the pending ownership proposal
+    CancelOwnershipProposal {},
 
     /// This accepts a properly-encoded ReceiveMsg from a cw20 contract (to process swapping request)
     Receive(Cw20ReceiveMsg),
 
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -57,7 +69,11 @@ pub struct ConfigResponse {
     /// Staking manager contract address
     pub staking_manager_addr: String,
     /// Swap fee
-    pub swap_fee: Uint128
+    pub swap_fee: Uint128,
+    /// Proposed new owner, waiting to accept the ownership
+    pub pending_owner: Option<String>,
+    /// The ownership proposal can no longer be accepted after this time
+    pub pending_owner_expires: Option<Timestamp>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
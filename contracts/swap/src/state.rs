@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -27,7 +27,17 @@ pub struct Supply {
     pub claims: Uint128,
 }
 
+/// OwnershipProposal is a pending transfer of the owner role, it takes effect once accepted by the new owner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    /// Proposed new owner
+    pub owner: Addr,
+    /// The proposal can no longer be accepted after this time
+    pub expires: Option<Timestamp>,
+}
+
 pub const CONFIG: Item<ConfigInfo> = Item::new("config");
 pub const TOTAL_SUPPLY: Item<Supply> = Item::new("total_supply");
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 pub const CLAIMABLE: Map<&Addr, Uint128> = Map::new("claimable");
 pub const QUEUE_ID: Map<&Addr, u64> = Map::new("queue_id");
\ No newline at end of file
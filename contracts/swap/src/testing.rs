@@ -0,0 +1,139 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::testing::{
+    mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    coin, coins, from_binary, to_binary, Attribute, Coin, ContractResult, Decimal, OwnedDeps,
+    SubMsg, SystemResult, Uint128,
+};
+
+use crate::bindings::{BindingMsg, BindingQuery, RewardsRecord, RewardsRecordsResponse};
+use crate::contract::{execute, instantiate, query};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, StatusResponse};
+use crate::ContractError;
+
+const DENOM: &str = "uconst";
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier<BindingQuery>, BindingQuery>;
+
+// mock_deps_with_records returns `records` as the Archway rewards records of the contract
+fn mock_deps_with_records(records: Vec<RewardsRecord>) -> MockDeps {
+    let mut querier =
+        MockQuerier::<BindingQuery>::new(&[]).with_custom_handler(move |query| match query {
+            BindingQuery::RewardsRecords {
+                rewards_address, ..
+            } => {
+                assert_eq!(rewards_address, MOCK_CONTRACT_ADDR);
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&RewardsRecordsResponse {
+                        records: records.clone(),
+                    })
+                    .unwrap(),
+                ))
+            }
+        });
+    querier.update_staking(DENOM, &[], &[]);
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+fn rewards_record(id: u64, rewards: Vec<Coin>) -> RewardsRecord {
+    RewardsRecord {
+        id,
+        rewards_address: MOCK_CONTRACT_ADDR.to_string(),
+        rewards,
+        calculated_height: 12345,
+        calculated_time: "2022-10-01T00:00:00Z".to_string(),
+    }
+}
+
+// setup instantiates the pool and adds `amount` native tokens of liquidity
+fn setup(deps: &mut MockDeps, amount: u128) {
+    let msg = InstantiateMsg {
+        liquid_token_addr: "liquidtoken".to_string(),
+        staking_manager_addr: "staking".to_string(),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(amount, DENOM));
+    let info = mock_info("provider", &coins(amount, DENOM));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Add {}).unwrap();
+}
+
+fn query_status(deps: &MockDeps) -> StatusResponse {
+    from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::StatusInfo {}).unwrap()).unwrap()
+}
+
+#[test]
+fn withdraw_archway_rewards_grows_pool() {
+    let records = vec![
+        rewards_record(1, coins(30, DENOM)),
+        rewards_record(2, vec![coin(20, DENOM), coin(500, "uother")]),
+    ];
+    let mut deps = mock_deps_with_records(records);
+    setup(&mut deps, 1000);
+    assert_eq!(query_status(&deps).ratio, Decimal::one());
+
+    let info = mock_info("anyone", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawArchwayRewards {},
+    )
+    .unwrap();
+    let withdraw = BindingMsg::WithdrawRewards {
+        records_limit: None,
+        record_ids: vec![1, 2],
+    };
+    assert_eq!(res.messages, vec![SubMsg::new(withdraw)]);
+    assert!(res.attributes.contains(&Attribute::new("rewards", "50")));
+
+    // the pool is the native balance of the contract, the withdrawn rewards go to the liquidity providers
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1050, DENOM));
+    let status = query_status(&deps);
+    assert_eq!(status.issued, Uint128::new(1000));
+    assert_eq!(status.balance, Uint128::new(1050));
+    assert_eq!(status.ratio, Decimal::from_ratio(1050u128, 1000u128));
+}
+
+#[test]
+fn withdraw_archway_rewards_without_rewards() {
+    // no records to withdraw
+    let mut deps = mock_deps_with_records(vec![]);
+    setup(&mut deps, 1000);
+    let info = mock_info("anyone", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawArchwayRewards {},
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::NoArchwayRewards {}));
+
+    // records paying other denoms are withdrawn without rewards for the pool
+    let mut deps = mock_deps_with_records(vec![rewards_record(7, coins(500, "uother"))]);
+    setup(&mut deps, 1000);
+    let info = mock_info("anyone", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawArchwayRewards {},
+    )
+    .unwrap();
+    let withdraw = BindingMsg::WithdrawRewards {
+        records_limit: None,
+        record_ids: vec![7],
+    };
+    assert_eq!(res.messages, vec![SubMsg::new(withdraw)]);
+    assert!(res.attributes.contains(&Attribute::new("rewards", "0")));
+    assert_eq!(query_status(&deps).ratio, Decimal::one());
+}